@@ -1,7 +1,12 @@
+use std::f64::consts::TAU;
+
 use egui::Color32;
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_plot::{Line, Plot, PlotPoints, Polygon, VLine};
 
-use crate::widgets::fit_calculator::FitCalculator;
+use crate::{
+    fit::AngleRestriction,
+    widgets::fit_calculator::{FitCalculator, SavedScenario, Scenario},
+};
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -12,6 +17,10 @@ pub struct RupertApp {
 
     #[serde(skip)]
     fit_calculator: FitCalculator,
+    /// The polygons and settings that the fit calculator had when the app was last saved.
+    scenario: Option<Scenario>,
+    /// The scenarios that had been kept under a name.
+    saved_scenarios: Vec<SavedScenario>,
 }
 
 impl Default for RupertApp {
@@ -19,6 +28,8 @@ impl Default for RupertApp {
         Self {
             label: "Hello World!".to_owned(),
             fit_calculator: FitCalculator::new(),
+            scenario: None,
+            saved_scenarios: Vec::new(),
         }
     }
 }
@@ -31,17 +42,193 @@ impl RupertApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
-        } else {
-            Default::default()
+        let mut app: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        if let Some(scenario) = &app.scenario {
+            app.fit_calculator.load_scenario(scenario);
         }
+        app.fit_calculator
+            .set_saved_scenarios(std::mem::take(&mut app.saved_scenarios));
+        app
+    }
+}
+
+impl RupertApp {
+    fn ui_plot(&self, ui: &mut egui::Ui) {
+        let plot_data = self.fit_calculator.plot_data();
+        // Create plot lines
+        let lines: Vec<Line<'_>> = plot_data
+            .yss
+            .iter()
+            .enumerate()
+            .map(|(idx, ys)| {
+                Line::new(
+                    format!("j = {}", idx + 1),
+                    plot_data
+                        .xs
+                        .iter()
+                        .zip(ys.iter())
+                        .map(|(&x, &y)| [x, y])
+                        .collect::<PlotPoints<'_>>(),
+                )
+                .color(
+                    ui.style().visuals.weak_text_color.unwrap_or(
+                        ui.style()
+                            .visuals
+                            .text_color()
+                            .gamma_multiply(ui.style().visuals.weak_text_alpha),
+                    ),
+                )
+            })
+            .collect();
+        let min_line = Line::new(
+            "Minimum",
+            plot_data
+                .xs
+                .iter()
+                .zip(plot_data.min_ys.iter())
+                .map(|(&x, &y)| [x, y])
+                .collect::<PlotPoints<'_>>(),
+        )
+        .color(Color32::from_rgb(200, 100, 100));
+
+        // Shade the angles at which there is a fit
+        let (y_min, y_max) = plot_data
+            .yss
+            .iter()
+            .flatten()
+            .fold((0.0f64, 0.0f64), |(lo, hi), &y| (lo.min(y), hi.max(y)));
+        let bands: Vec<Polygon<'_>> = self
+            .fit_calculator
+            .feasible_intervals()
+            .iter()
+            .flat_map(|interval| {
+                // Intervals that wrap past 2π are drawn as two bands
+                let (start, end) = (interval.start, interval.end);
+                [(start, end.min(TAU)), (0.0, end - TAU)]
+            })
+            .filter(|(start, end)| end > start)
+            .map(|(start, end)| {
+                Polygon::new(
+                    "Feasible",
+                    PlotPoints::new(vec![
+                        [start, y_min],
+                        [end, y_min],
+                        [end, y_max],
+                        [start, y_max],
+                    ]),
+                )
+                .fill_color(Color32::from_rgb(100, 200, 100).linear_multiply(0.15))
+            })
+            .collect();
+
+        let (masks, fixed_angle) = self.restriction_masks(y_min, y_max);
+
+        Plot::new("cosine_plot_test")
+            .width(600.0)
+            .height(300.0)
+            .show(ui, |plot_ui| {
+                for band in bands {
+                    plot_ui.polygon(band);
+                }
+                for mask in masks {
+                    plot_ui.polygon(mask);
+                }
+                if let Some(line) = fixed_angle {
+                    plot_ui.vline(line);
+                }
+                for line in lines {
+                    plot_ui.line(line);
+                }
+                plot_ui.line(min_line);
+            });
+    }
+
+    /// Bands greying out the angles that B isn't allowed to turn to, between `y_min` and
+    /// `y_max`, and a line at the one angle it's allowed if it can't turn at all.
+    fn restriction_masks(&self, y_min: f64, y_max: f64) -> (Vec<Polygon<'_>>, Option<VLine>) {
+        let angles = &self.fit_calculator.restrictions().angles;
+        let masks = angles
+            .masked_intervals()
+            .into_iter()
+            .map(|(start, end)| {
+                Polygon::new(
+                    "Not allowed",
+                    PlotPoints::new(vec![
+                        [start, y_min],
+                        [end, y_min],
+                        [end, y_max],
+                        [start, y_max],
+                    ]),
+                )
+                .fill_color(Color32::GRAY.linear_multiply(0.25))
+                .stroke(egui::Stroke::NONE)
+            })
+            .collect();
+        let fixed_angle = match angles {
+            &AngleRestriction::Fixed(angle) => {
+                Some(VLine::new("Fixed angle", angle.rem_euclid(TAU)).color(Color32::GRAY))
+            }
+            _ => None,
+        };
+        (masks, fixed_angle)
+    }
+
+    /// The fit calculator and its plot, followed by the other tools in sections that can be
+    /// opened and closed.
+    fn ui_sections(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Rupert");
+        ui.label(
+            "Use the handles below to customise the shapes of the polygons, \
+            and explore how that affects the ability of one to fit within the other.",
+        );
+
+        ui.separator();
+
+        self.fit_calculator.ui_content(ui);
+
+        ui.separator();
+
+        self.ui_plot(ui);
+
+        ui.separator();
+
+        egui::CollapsingHeader::new("Parameter sweep").show(ui, |ui| {
+            self.fit_calculator.ui_sweep(ui);
+        });
+
+        egui::CollapsingHeader::new("Feasibility map").show(ui, |ui| {
+            self.fit_calculator.ui_feasibility_map(ui);
+        });
+
+        egui::CollapsingHeader::new("Random experiment").show(ui, |ui| {
+            self.fit_calculator.ui_experiment(ui);
+        });
+
+        egui::CollapsingHeader::new("Explorer").show(ui, |ui| {
+            self.fit_calculator.ui_explorer(ui);
+        });
+
+        egui::CollapsingHeader::new("Saved scenarios").show(ui, |ui| {
+            self.fit_calculator.ui_saved_scenarios(ui);
+        });
+
+        ui.separator();
+
+        ui.add(egui::github_link_file!(
+            "https://github.com/TimLeach635/rupert_gui/blob/main/",
+            "Source code."
+        ));
     }
 }
 
 impl eframe::App for RupertApp {
     /// Called by the framework to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.scenario = Some(self.fit_calculator.scenario());
+        self.saved_scenarios = self.fit_calculator.saved_scenarios().to_vec();
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 
@@ -71,75 +258,15 @@ impl eframe::App for RupertApp {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             // The central panel the region left after adding TopPanel's and SidePanel's
-            ui.heading("Rupert");
-            ui.label(
-                "Use the handles below to customise the shapes of the polygons, \
-                and explore how that affects the ability of one to fit within the other.",
-            );
-
-            ui.separator();
-
-            self.fit_calculator.ui_content(ui);
-
-            ui.separator();
-
-            let plot_data = self.fit_calculator.plot_data();
-            // Create plot lines
-            let lines: Vec<Line<'_>> = plot_data
-                .yss
-                .iter()
-                .enumerate()
-                .map(|(idx, ys)| {
-                    Line::new(
-                        format!("j = {}", idx + 1),
-                        plot_data
-                            .xs
-                            .iter()
-                            .zip(ys.iter())
-                            .map(|(&x, &y)| [x as f64, y as f64])
-                            .collect::<PlotPoints<'_>>(),
-                    )
-                    .color(
-                        ui.style().visuals.weak_text_color.unwrap_or(
-                            ui.style()
-                                .visuals
-                                .text_color()
-                                .gamma_multiply(ui.style().visuals.weak_text_alpha),
-                        ),
-                    )
-                })
-                .collect();
-            let min_line = Line::new(
-                "Minimum",
-                plot_data
-                    .xs
-                    .iter()
-                    .zip(plot_data.min_ys.iter())
-                    .map(|(&x, &y)| [x as f64, y as f64])
-                    .collect::<PlotPoints<'_>>(),
-            )
-            .color(Color32::from_rgb(200, 100, 100));
-
-            Plot::new("cosine_plot_test")
-                .width(600.0)
-                .height(300.0)
-                .show(ui, |plot_ui| {
-                    for line in lines {
-                        plot_ui.line(line);
-                    }
-                    plot_ui.line(min_line);
-                });
-
-            ui.separator();
-
-            ui.add(egui::github_link_file!(
-                "https://github.com/TimLeach635/rupert_gui/blob/main/",
-                "Source code."
-            ));
-
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 powered_by_egui_and_eframe(ui);
                 egui::warn_if_debug_build(ui);
+
+                // Everything else scrolls in the space above, as it doesn't all fit once a
+                // few of the sections are open
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.ui_sections(ui));
+                });
             });
         });
     }
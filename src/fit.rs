@@ -0,0 +1,1316 @@
+use std::cmp::Ordering;
+
+use egui::Pos2;
+use itertools::{Itertools as _, repeat_n};
+use nalgebra::{RealField, Vector2, Vector3, vector};
+
+use crate::geometry::{half_planes, symmetry};
+
+mod centre;
+mod exact;
+mod explorer;
+mod feasibility_map;
+mod lp;
+mod monte_carlo;
+mod optimise;
+mod overflow;
+mod parallel;
+mod parameter_sweep;
+mod pre_check;
+mod restrictions;
+mod sensitivity;
+mod solver;
+#[cfg(test)]
+mod test_support;
+mod verify;
+pub use centre::{feasible_region, region_centroid};
+pub use exact::exact_margin_sign;
+pub use explorer::{Explorer, Find, SearchCriterion, SearchStrategy};
+pub use feasibility_map::{FeasibilityMap, MapAxis, MapCell};
+pub use lp::{max_min_slack, max_min_slack_within};
+pub use monte_carlo::{PolygonDistribution, PolygonFamily, RandomExperiment, TrialPair};
+pub use optimise::{ShapeConstraints, ShapeObjective, ShapeOptimiser};
+pub use overflow::{Overflow, min_overflow};
+pub use parameter_sweep::{ParameterSweep, SweepOutcome, SweepParameter, SweepPoint};
+pub use pre_check::{PreCheck, PreCheckResult, pre_check};
+pub use restrictions::{AngleInterval, AngleRestriction, Restrictions, TranslationBound};
+pub use sensitivity::{Sensitivity, SensitivityAnalysis, VertexSensitivity};
+pub use solver::{
+    CriticalRegionSolver, FitSolver, GridSearchSolver, LinearProgramSolver, SolverKind, solve,
+};
+pub use verify::{Certificate, CertificateEntry, Verdict, verify};
+
+/// A floating point type that the solver can do its sums in.
+pub trait Real: RealField + Copy {
+    fn from_double(x: f64) -> Self;
+    fn to_double(self) -> f64;
+
+    fn from_single(x: f32) -> Self {
+        Self::from_double(f64::from(x))
+    }
+}
+
+impl Real for f32 {
+    fn from_double(x: f64) -> Self {
+        x as Self
+    }
+
+    fn to_double(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl Real for f64 {
+    fn from_double(x: f64) -> Self {
+        x
+    }
+
+    fn to_double(self) -> f64 {
+        self
+    }
+}
+
+/// One of the two polygons: A, which B has to fit inside, or B.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Which {
+    Outer,
+    #[default]
+    Inner,
+}
+
+impl Which {
+    pub const ALL: [Self; 2] = [Self::Outer, Self::Inner];
+
+    /// The letter the polygon goes by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Outer => "A",
+            Self::Inner => "B",
+        }
+    }
+}
+
+pub enum Fit<T = f64> {
+    NoFit,
+    /// The solver can't handle these polygons, so there's no answer either way.
+    Unsupported,
+    Fit {
+        angle: T,
+        translation: Vector2<T>,
+        active_constraints: Vec<ActiveConstraint<T>>,
+    },
+}
+
+/// One of the `(i, j)` pairs that make the fit tight: at the fitted pose, inner vertex `j`
+/// lies `slack` units inside the line through outer edge `i` (moved inwards by any
+/// clearance).
+///
+/// Edge `i` is the one that ends at outer vertex `i`.
+#[derive(Clone, Copy, Debug)]
+pub struct ActiveConstraint<T = f64> {
+    pub edge: usize,
+    pub vertex: usize,
+    pub slack: T,
+}
+
+#[derive(Clone)]
+pub struct PlotData<T = f64> {
+    pub xs: Vec<T>,
+    pub yss: Vec<Vec<T>>,
+    pub min_ys: Vec<T>,
+    /// At each angle in `xs`, the translation that leaves B the most room inside A.
+    pub translations: Vec<Vector2<T>>,
+    /// At each angle in `xs`, the smallest gap between B and A at the best translation.
+    pub slacks: Vec<T>,
+    /// The angles that `xs` covers.
+    pub domain: AngleDomain<T>,
+}
+
+/// The range of angles that a sweep has to cover to see every distinct pose, once any
+/// symmetry shared by the two polygons has been taken into account.
+#[derive(Clone, Copy, Debug)]
+pub struct AngleDomain<T = f64> {
+    pub start: T,
+    pub length: T,
+    /// Whether the curves repeat every `length`, so that the end of the domain joins back up
+    /// with its start. Otherwise, the curves are mirrored about both ends.
+    pub periodic: bool,
+}
+
+impl<T: Real> AngleDomain<T> {
+    pub fn full_turn() -> Self {
+        Self {
+            start: T::zero(),
+            length: T::two_pi(),
+            periodic: true,
+        }
+    }
+
+    /// The smallest domain that the symmetries of the two polygons allow.
+    ///
+    /// If A looks the same turned through `2π / k_a` and B through `2π / k_b`, then turning
+    /// B through either leaves the fit unchanged (after turning the whole picture back, for
+    /// A's), so the curves repeat every `2π / lcm(k_a, k_b)`. If both have a mirror line as
+    /// well, at angles `a` and `b`, then reflecting the whole picture in A's line takes B at
+    /// angle θ to B at angle `2(b - a) - θ`, so only half of each repeat is needed.
+    pub fn of(outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> Self {
+        let outer = symmetry(outer_vertices);
+        let inner = symmetry(inner_vertices);
+        let order = outer.order / gcd(outer.order, inner.order) * inner.order;
+        let period = std::f64::consts::TAU / order as f64;
+
+        match (outer.mirror_axes.first(), inner.mirror_axes.first()) {
+            (Some(&a), Some(&b)) => {
+                let half_period = period / 2.0;
+                Self {
+                    start: T::from_double(f64::from(b - a).rem_euclid(half_period)),
+                    length: T::from_double(half_period),
+                    periodic: false,
+                }
+            }
+            _ => Self {
+                start: T::zero(),
+                length: T::from_double(period),
+                periodic: true,
+            },
+        }
+    }
+
+    /// `intervals` found within the domain, along with their images under the symmetries
+    /// that cut the sweep down to the domain, so that between them they cover the whole
+    /// turn.
+    ///
+    /// As with any other feasible intervals, each starts within `[0, 2π)`, and those that
+    /// meet (including across 2π) are joined up.
+    pub fn unfold(&self, intervals: &[FeasibleInterval<T>]) -> Vec<FeasibleInterval<T>> {
+        let two_pi = T::two_pi();
+        // Mirrored domains are reflected about their start to make up a whole period
+        let axis = self.start + self.start;
+        let mirrored = intervals.iter().map(|interval| FeasibleInterval {
+            start: axis - interval.end,
+            end: axis - interval.start,
+            peak_angle: axis - interval.peak_angle,
+            peak_margin: interval.peak_margin,
+        });
+        let (period, one_period) = if self.periodic {
+            (self.length, intervals.to_vec())
+        } else {
+            let one_period = intervals.iter().copied().chain(mirrored).collect_vec();
+            (self.length + self.length, one_period)
+        };
+
+        let num_periods = ((two_pi / period).round().to_double() as usize).max(1);
+        let mut unfolded = (0..num_periods)
+            .flat_map(|m| {
+                let shift = period * T::from_double(m as f64);
+                one_period.iter().map(move |interval| {
+                    let start = interval.start + shift;
+                    let shift = shift - (start / two_pi).floor() * two_pi;
+                    FeasibleInterval {
+                        start: interval.start + shift,
+                        end: interval.end + shift,
+                        peak_angle: interval.peak_angle + shift,
+                        peak_margin: interval.peak_margin,
+                    }
+                })
+            })
+            .collect_vec();
+        unfolded.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(Ordering::Equal));
+
+        // The copies only meet up to round-off
+        let slack = two_pi * T::from_double(1e-9);
+        let mut joined: Vec<FeasibleInterval<T>> = Vec::with_capacity(unfolded.len());
+        for interval in unfolded {
+            match joined.last_mut() {
+                Some(last) if interval.start <= last.end + slack => {
+                    *last = joined_up(*last, interval, T::zero());
+                }
+                _ => joined.push(interval),
+            }
+        }
+        if joined.len() > 1
+            && let (Some(&first), Some(&last)) = (joined.first(), joined.last())
+            && first.start + two_pi <= last.end + slack
+        {
+            joined.remove(0);
+            if let Some(last) = joined.last_mut() {
+                *last = joined_up(*last, first, two_pi);
+            }
+        }
+        joined
+    }
+}
+
+/// `earlier` stretched to cover `later` as well, once `later` has been moved on by `shift`.
+fn joined_up<T: Real>(
+    earlier: FeasibleInterval<T>,
+    later: FeasibleInterval<T>,
+    shift: T,
+) -> FeasibleInterval<T> {
+    let (peak_angle, peak_margin) = if later.peak_margin > earlier.peak_margin {
+        (later.peak_angle + shift, later.peak_margin)
+    } else {
+        (earlier.peak_angle, earlier.peak_margin)
+    };
+    FeasibleInterval {
+        start: earlier.start,
+        end: earlier.end.max(later.end + shift),
+        peak_angle,
+        peak_margin,
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a.max(1) } else { gcd(b, a % b) }
+}
+
+/// A contiguous range of angles over which the minimum curve is positive, i.e. over which
+/// the inner polygon can be placed inside the outer one.
+///
+/// Intervals that wrap around from 2π back to 0 are reported with `end > 2π`, so that
+/// `start <= end` always holds. The two are equal when B isn't allowed to turn.
+#[derive(Clone, Copy, Debug)]
+pub struct FeasibleInterval<T = f64> {
+    pub start: T,
+    pub end: T,
+    /// The angle within the interval at which the minimum curve is highest.
+    pub peak_angle: T,
+    /// The value of the minimum curve at `peak_angle`.
+    pub peak_margin: T,
+}
+
+impl<T: Real> PlotData<T> {
+    fn empty() -> Self {
+        Self {
+            xs: Vec::new(),
+            yss: Vec::new(),
+            min_ys: Vec::new(),
+            translations: Vec::new(),
+            slacks: Vec::new(),
+            domain: AngleDomain::full_turn(),
+        }
+    }
+}
+
+/// The verdict of evaluating the minimum curve in exact arithmetic at a single angle.
+#[derive(Clone, Copy, Debug)]
+pub struct ExactCheck {
+    pub angle: f64,
+    /// [`Ordering::Greater`] if B fits with room to spare, [`Ordering::Equal`] if it fits
+    /// but touches A, and [`Ordering::Less`] if it doesn't fit at this angle.
+    pub sign: Ordering,
+}
+
+pub struct FitData<T = f64> {
+    pub fit: Fit<T>,
+    pub plot_data: PlotData<T>,
+    /// Every range of angles over which B fits, over the whole turn, even where symmetry cut
+    /// the sweep down to part of it.
+    pub feasible_intervals: Vec<FeasibleInterval<T>>,
+    /// The pre-check that settled the answer, if there was one. If it ruled B out, the sweep
+    /// was skipped and there is nothing to plot.
+    pub decided_by: Option<PreCheck>,
+    /// The exact check that settled a borderline answer, if there was one.
+    pub exact_check: Option<ExactCheck>,
+    /// The interval arithmetic proof of the answer, if one was asked for.
+    pub certificate: Option<Certificate>,
+    /// The largest gap that can be left between every vertex of B and every edge of A, at
+    /// the best angle found, over and above the clearance asked for. Negative when B doesn't
+    /// fit.
+    pub margin: Option<T>,
+    /// The largest clearance that B could be given and still fit, which is the margin plus
+    /// the clearance asked for. Negative when B doesn't fit even touching A.
+    pub max_clearance: Option<T>,
+    /// Whether the answer is for B reflected left to right (before it is turned), rather
+    /// than B as drawn.
+    pub mirrored: bool,
+    /// How B fared each way round, if mirroring was allowed.
+    pub orientations: Option<OrientationComparison<T>>,
+    /// Where B overflows A the least, if it doesn't fit.
+    pub overflow: Option<Overflow>,
+}
+
+impl<T: Real> FitData<T> {
+    /// The answer from a solver that can't handle the polygons it's been given.
+    fn unsupported() -> Self {
+        Self {
+            fit: Fit::Unsupported,
+            plot_data: PlotData::empty(),
+            feasible_intervals: Vec::new(),
+            decided_by: None,
+            exact_check: None,
+            certificate: None,
+            margin: None,
+            max_clearance: None,
+            mirrored: false,
+            orientations: None,
+            overflow: None,
+        }
+    }
+
+    /// The answer when a pre-check has shown that B can't fit, without sweeping.
+    fn ruled_out_by(check: PreCheck) -> Self {
+        Self {
+            fit: Fit::NoFit,
+            decided_by: Some(check),
+            ..Self::unsupported()
+        }
+    }
+}
+
+/// The margin that B could be given each way round, when it's allowed to be mirrored.
+#[derive(Clone, Copy, Debug)]
+pub struct OrientationComparison<T = f64> {
+    pub as_drawn: Option<T>,
+    pub mirrored: Option<T>,
+}
+
+/// Options that control how [`calculate_data`] goes about its work.
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SolverSettings {
+    /// Whether to try the quick tests in [`pre_check`] before sweeping through the angles.
+    pub pre_checks: bool,
+    /// Whether to settle borderline answers by redoing the sums in exact arithmetic at the
+    /// best angle found, rather than trusting floating point.
+    pub exact: bool,
+    /// Whether to try to prove the answer with [`verify`].
+    pub verify: bool,
+    /// How many times [`verify`] may halve a piece of the angles before giving up on it.
+    pub verify_depth: u32,
+    /// Whether to sample the angles more finely where the minimum curve needs it, rather
+    /// than evenly.
+    pub adaptive: bool,
+    /// How far the minimum curve may stray from a straight line between neighbouring
+    /// samples before [`Self::adaptive`] sampling adds another between them.
+    pub tolerance: f64,
+    /// Whether to sweep only the angles that the polygons' symmetries say are distinct.
+    pub use_symmetry: bool,
+    /// Whether B may be flipped over as well as turned.
+    pub allow_mirroring: bool,
+    /// Which angles and translations B is allowed, beyond having to fit.
+    pub restrictions: Restrictions,
+    /// How far B has to be kept from every edge of A.
+    pub clearance: f64,
+    /// Which of the poses that fit to report.
+    pub objective: PoseObjective,
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        Self {
+            pre_checks: true,
+            exact: false,
+            verify: false,
+            verify_depth: 12,
+            adaptive: true,
+            tolerance: 1e-2,
+            use_symmetry: true,
+            allow_mirroring: false,
+            restrictions: Restrictions::default(),
+            clearance: 0.0,
+            objective: PoseObjective::default(),
+        }
+    }
+}
+
+/// What makes one pose that fits better than another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum PoseObjective {
+    /// Leave as much room as possible between B and the nearest edge of A.
+    #[default]
+    MaxClearance,
+    /// Put B in the middle of the range of angles it fits at, and then in the middle of the
+    /// region it can slide about in at that angle.
+    Centred,
+}
+
+impl PoseObjective {
+    pub const ALL: [Self; 2] = [Self::MaxClearance, Self::Centred];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MaxClearance => "Max clearance",
+            Self::Centred => "Centred",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::MaxClearance => "Keep B as far as possible from the nearest edge of A",
+            Self::Centred => "Put B in the middle of the angles and positions at which it fits",
+        }
+    }
+
+    /// The pose this objective prefers, starting from the pose with the most clearance at
+    /// `best_angle`. `constraints_at` gives the half-planes that the translation has to
+    /// stay within at a given angle, and `period` is how often the intervals repeat, if
+    /// they do.
+    fn preferred_pose<T: Real>(
+        self,
+        best_angle: T,
+        best_translation: Vector2<T>,
+        intervals: &[FeasibleInterval<T>],
+        period: Option<T>,
+        constraints_at: impl Fn(T) -> Vec<(Vector2<T>, T)>,
+    ) -> (T, Vector2<T>) {
+        match self {
+            Self::MaxClearance => (best_angle, best_translation),
+            Self::Centred => {
+                let contains = |interval: &FeasibleInterval<T>, angle: T| {
+                    interval.start <= angle && angle <= interval.end
+                };
+                let angle = intervals
+                    .iter()
+                    .find(|interval| {
+                        contains(interval, best_angle)
+                            || period.is_some_and(|period| contains(interval, best_angle + period))
+                    })
+                    .map_or(best_angle, |interval| {
+                        let middle = (interval.start + interval.end) / T::from_double(2.0);
+                        match period {
+                            Some(period) if middle >= period => middle - period,
+                            _ => middle,
+                        }
+                    });
+                let translation =
+                    region_centroid(&constraints_at(angle)).unwrap_or(best_translation);
+                (angle, translation)
+            }
+        }
+    }
+}
+
+/// How many angles to sample, evenly spread over a whole turn, when not sampling
+/// adaptively.
+const NUM_POINTS: usize = 400;
+/// How many evenly spread angles adaptive sampling starts from.
+const ADAPTIVE_START_POINTS: usize = 64;
+/// How many times adaptive sampling may halve the gap between two of its starting angles.
+const MAX_REFINEMENT_DEPTH: u32 = 16;
+/// How closely (in radians) adaptive sampling pins down the angles at which the minimum
+/// curve crosses zero or switches from one curve to another.
+const KINK_RESOLUTION: f64 = 1e-4;
+
+/// How close to zero (relative to the size of the polygons) the best value of the minimum
+/// curve has to be for the exact check to be worth running.
+const BORDERLINE: f64 = 1e-4;
+
+/// How many times the round-off error the minimum curve has to rise above zero by (relative
+/// to the size of the polygons) for B to count as fitting. The verdict and the feasible
+/// intervals both go by this, so that they can't disagree.
+const ROUND_OFF_MULTIPLE: f64 = 1000.0;
+
+/// The value the minimum curve has to rise above for B to count as fitting, when the curves
+/// are about as big as `scale`.
+fn fit_threshold<T: Real>(scale: T) -> T {
+    scale * T::default_epsilon() * T::from_double(ROUND_OFF_MULTIPLE)
+}
+
+/// How far inside the half-plane `(n, c)` the inner vertex `b` lies, once the inner polygon
+/// has been rotated by `angle`.
+///
+/// As a function of `angle`, this is one of the cosine curves that make up the plot.
+fn clearance<T: Real>((n, c): (Vector2<T>, T), b: Vector2<T>, angle: T) -> T {
+    let cos_multiplier = n.dot(&b);
+    let sin_multiplier = n.x * b.y - b.x * n.y;
+    c - (cos_multiplier * angle.cos() + sin_multiplier * angle.sin())
+}
+
+fn to_vectors<T: Real>(vertices: &[Pos2]) -> Vec<Vector2<T>> {
+    vertices
+        .iter()
+        .map(|v| Vector2::new(T::from_single(v.x), T::from_single(v.y)))
+        .collect()
+}
+
+/// The [`half_planes`] of the outer polygon, each moved inwards by `clearance`, so that the
+/// inner polygon fits inside them when it fits with at least that much room to spare.
+fn inset_half_planes<T: Real>(
+    outer_vertices: &[Vector2<T>],
+    clearance: f64,
+) -> Vec<(Vector2<T>, T)> {
+    let clearance = T::from_double(clearance);
+    half_planes(outer_vertices)
+        .into_iter()
+        .map(|(n, c)| (n, c - clearance))
+        .collect()
+}
+
+/// For each outer edge, the inner vertex that comes closest to it and how far inside it
+/// lies, once the inner polygon has been rotated by `angle`.
+fn tightest_vertices<T: Real>(
+    planes: &[(Vector2<T>, T)],
+    inner_vertices: &[Vector2<T>],
+    angle: T,
+) -> Vec<(usize, T)> {
+    planes
+        .iter()
+        .map(|&plane| {
+            inner_vertices
+                .iter()
+                .map(|&b| clearance(plane, b, angle))
+                .enumerate()
+                .reduce(|min, pair| if pair.1 < min.1 { pair } else { min })
+                .unwrap_or((0, T::zero()))
+        })
+        .collect()
+}
+
+/// For each outer edge `(n, c)`, the half-plane `(n, g)` that the translation `t` has to stay
+/// within, `n.dot(t) <= g`, for the inner polygon to fit once it's rotated by `angle`.
+fn room_constraints<T: Real>(
+    planes: &[(Vector2<T>, T)],
+    inner_vertices: &[Vector2<T>],
+    angle: T,
+) -> Vec<(Vector2<T>, T)> {
+    planes
+        .iter()
+        .zip(tightest_vertices(planes, inner_vertices, angle))
+        .map(|(&(n, _), (_, clearance))| (n, clearance))
+        .collect()
+}
+
+/// Works out whether the inner polygon fits inside the outer one, in double precision.
+///
+/// Both sets of vertices are expected to be centred on their centroids, and defined
+/// anti-clockwise.
+pub fn calculate_data(
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    settings: &SolverSettings,
+) -> FitData {
+    calculate_data_with::<f64>(outer_vertices, inner_vertices, settings)
+}
+
+/// Works out whether the inner polygon fits inside the outer one, doing the sums in `T`.
+///
+/// Both sets of vertices are expected to be centred on their centroids, and defined
+/// anti-clockwise.
+pub fn calculate_data_with<T: Real>(
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    settings: &SolverSettings,
+) -> FitData<T> {
+    let restrictions = &settings.restrictions;
+    let planes = inset_half_planes(&to_vectors(outer_vertices), settings.clearance);
+    let Some(region) = CriticalRegion::new(planes, to_vectors(inner_vertices)) else {
+        return FitData::unsupported();
+    };
+    let region = region.within(restrictions.translation.as_ref());
+
+    // None of the shortcuts know about the restrictions or the clearance, so they're only
+    // taken without any
+    let unrestricted = !restrictions.is_active() && settings.clearance <= 0.0;
+    let certificate = (settings.verify && unrestricted)
+        .then(|| verify(outer_vertices, inner_vertices, settings.verify_depth))
+        .flatten();
+
+    // A pre-check that rules B out settles everything, but one that shows B fits still
+    // leaves the sweep to find the margin and the angles that fit
+    let pre_checked = (settings.pre_checks && unrestricted)
+        .then(|| pre_check(outer_vertices, inner_vertices))
+        .flatten();
+    if let Some(result) = &pre_checked
+        && !result.check.fits()
+    {
+        return FitData {
+            certificate,
+            ..FitData::ruled_out_by(result.check)
+        };
+    }
+
+    let data = swept_data(&region, outer_vertices, inner_vertices, settings);
+    let fits = matches!(data.fit, Fit::Fit { .. });
+    FitData {
+        decided_by: pre_checked.map(|result| result.check).filter(|_| fits),
+        certificate,
+        ..data
+    }
+}
+
+/// Sweeps through the angles, finding where B fits in the critical region and by how much.
+fn swept_data<T: Real>(
+    region: &CriticalRegion<T>,
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    settings: &SolverSettings,
+) -> FitData<T> {
+    let restrictions = &settings.restrictions;
+    let unrestricted = !restrictions.is_active() && settings.clearance <= 0.0;
+    let domain = if settings.use_symmetry && unrestricted {
+        AngleDomain::of(outer_vertices, inner_vertices)
+    } else {
+        AngleDomain::full_turn()
+    };
+    let samples = region.sweep(settings, domain);
+    let xs: Vec<T> = samples.iter().map(|sample| sample.x).collect();
+    let min_ys: Vec<T> = samples.iter().map(|sample| sample.min_y).collect();
+    let mut yss: Vec<Vec<T>> = vec![Vec::with_capacity(samples.len()); region.index_choices.len()];
+    for sample in &samples {
+        for (ys, &y) in yss.iter_mut().zip(&sample.ys) {
+            ys.push(y);
+        }
+    }
+
+    // The allowed value of x that maximises the minimum, the minimum there, and the index
+    // choice that attains it
+    let max_min = samples
+        .iter()
+        .filter(|sample| restrictions.angles.allows(sample.x.to_double()))
+        .reduce(|best, next| if next.min_y > best.min_y { next } else { best })
+        .map(|sample| (sample.x, sample.min_y, sample.choice));
+
+    let period = domain.periodic.then_some(domain.length);
+    let threshold = fit_threshold(region.scale());
+    let intervals = feasible_intervals(&xs, &min_ys, period, threshold);
+    let feasible_intervals = restrictions.angles.clip(intervals, &xs, &min_ys, threshold);
+
+    // If this minimum line ever rises above zero, there is a fit!
+    // By finding the value of x that attains this maximum, we are in some way recording
+    // the "best" fit, for some definition of "best".
+    // Floating point makes the answer flicker when the best value is very close to zero, so
+    // we either demand a little more than round-off error, or (if asked to) settle it exactly.
+    let mut exact_check = None;
+    let fit = match max_min {
+        Some((angle, max_min_y, indices)) => {
+            let scale = region.scale();
+            let borderline = max_min_y.abs() <= scale * T::from_double(BORDERLINE);
+            let does_fit = if settings.exact && unrestricted && borderline {
+                let check = region.exact_check(outer_vertices, inner_vertices, angle);
+                exact_check = Some(check);
+                check.sign != Ordering::Less
+            } else {
+                max_min_y > threshold
+            };
+
+            if does_fit {
+                let (translation, _) = region.roomiest_pose(angle);
+                let (angle, translation) = settings.objective.preferred_pose(
+                    angle,
+                    translation,
+                    &feasible_intervals,
+                    period,
+                    |angle| region.translation_constraints(angle),
+                );
+                let indices = region.sample(angle).map_or(indices, |sample| sample.choice);
+                region.fit_at(angle, indices, translation)
+            } else {
+                Fit::NoFit
+            }
+        }
+        None => Fit::NoFit,
+    };
+
+    let (translations, slacks) = xs.iter().map(|&x| region.roomiest_pose(x)).unzip();
+
+    // At any one angle, the gaps `s` to each edge can be anything with `n_p.dot(s) = y`, so
+    // the smallest of them is largest when they're all `y / n_p.sum()` (and `sample` scales
+    // the room left within any translation bound to match)
+    let margin = max_min.map(|(_, max_min_y, _)| max_min_y / region.n_p.sum());
+    let max_clearance = margin.map(|margin| margin + T::from_double(settings.clearance));
+
+    FitData {
+        fit,
+        plot_data: PlotData {
+            xs,
+            yss,
+            min_ys,
+            translations,
+            slacks,
+            domain,
+        },
+        feasible_intervals: domain.unfold(&feasible_intervals),
+        decided_by: None,
+        exact_check,
+        certificate: None,
+        margin,
+        max_clearance,
+        mirrored: false,
+        orientations: None,
+        overflow: None,
+    }
+}
+
+/// The value of every curve at one angle, and which of them is lowest there.
+struct Sample<'a, T> {
+    x: T,
+    ys: Vec<T>,
+    min_y: T,
+    /// The index choice of the lowest curve.
+    choice: &'a [usize],
+}
+
+/// Everything about the outer polygon's edges and the inner polygon's vertices that's needed
+/// to evaluate the cosine curves.
+struct CriticalRegion<T: Real> {
+    planes: Vec<(Vector2<T>, T)>,
+    inner_vertices: Vec<Vector2<T>>,
+    /// The normal to the critical plane.
+    n_p: Vector3<T>,
+    index_choices: Vec<Vec<usize>>,
+    /// The half-planes that the translation has to stay within, if it's restricted.
+    bounds: Vec<(Vector2<T>, T)>,
+    /// A translation within `bounds` to start looking from.
+    bounds_origin: Vector2<T>,
+}
+
+impl<T: Real> CriticalRegion<T> {
+    /// The critical region for a triangular outer polygon, or `None` for any other.
+    fn new(planes: Vec<(Vector2<T>, T)>, inner_vertices: Vec<Vector2<T>>) -> Option<Self> {
+        // Find the critical region
+        // TODO: Generalise to N_A > 3
+        let &[(n_1, _), (n_2, _), (n_3, _)] = planes.as_slice() else {
+            return None;
+        };
+        let n_p: Vector3<T> = vector![
+            n_2.x * n_3.y - n_3.x * n_2.y,
+            n_3.x * n_1.y - n_1.x * n_3.y,
+            n_1.x * n_2.y - n_2.x * n_1.y,
+        ];
+        assert!(
+            n_p.iter().all(|&n| n >= T::zero()),
+            "The outer polygon should be defined anti-clockwise"
+        );
+
+        // We need to generate a potentially very large number of lines here: there are N_B
+        // to the power of N_A different cosine curves that we need to consider.
+        // We can generate a list of indices for these curves by getting "permutations with
+        // replacement", which according to
+        // https://docs.rs/itertools/latest/itertools/trait.Itertools.html#method.permutations
+        // is done with the following:
+        let index_choices = repeat_n(0..inner_vertices.len(), planes.len())
+            .multi_cartesian_product()
+            .collect_vec();
+
+        Some(Self {
+            planes,
+            inner_vertices,
+            n_p,
+            index_choices,
+            bounds: Vec::new(),
+            bounds_origin: Vector2::zeros(),
+        })
+    }
+
+    /// Keeps the translation within `bound`, if there is one.
+    fn within(self, bound: Option<&TranslationBound>) -> Self {
+        match bound {
+            Some(bound) => Self {
+                bounds: bound.half_planes(),
+                bounds_origin: bound.interior_point(),
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    fn inner_vertex(&self, j: usize) -> Vector2<T> {
+        self.inner_vertices
+            .get(j)
+            .copied()
+            .expect("Index choices should only contain inner vertex indices")
+    }
+
+    /// Each "index choice" is a vector of length `N_A`, where each element is an index from 0
+    /// to (`N_B` - 1) indicating which choice of "j" (i.e. which inner polygon vertex) to use
+    /// for the corresponding curve.
+    /// We then plug all those choices into the dot product with the critical region's normal
+    /// vector.
+    /// In the triangle case, this is only a single plane with a single normal, so there is
+    /// only one dot product, but the number of planes grows with the number of sides of the
+    /// polygons (roughly with the cube of `N_A`).
+    fn critical_value(&self, index_choice: &[usize], x: T) -> T {
+        index_choice
+            .iter()
+            .zip(&self.planes)
+            .zip(self.n_p.iter())
+            .fold(T::zero(), |sum, ((&j, &plane), &n_p_i)| {
+                sum + n_p_i * clearance(plane, self.inner_vertex(j), x)
+            })
+    }
+
+    fn sample(&self, x: T) -> Option<Sample<'_, T>> {
+        let ys = self
+            .index_choices
+            .iter()
+            .map(|index_choice| self.critical_value(index_choice, x))
+            .collect_vec();
+        let (&min_y, choice) = ys
+            .iter()
+            .zip(&self.index_choices)
+            .reduce(|min, pair| if pair.0 < min.0 { pair } else { min })?;
+        // If the translation is bounded, B may not be able to get to the roomiest spot, so
+        // use however much room it can get instead, in the same units as the curves
+        let min_y = if self.bounds.is_empty() {
+            min_y
+        } else {
+            min_y.min(self.roomiest_pose(x).1 * self.n_p.sum())
+        };
+        Some(Sample {
+            x,
+            ys,
+            min_y,
+            choice,
+        })
+    }
+
+    /// Samples the curves over a whole turn: either evenly, or (if the settings ask for it)
+    /// starting evenly and then filling in wherever the minimum curve needs more detail. The
+    /// ends of any allowed ranges of angles are always sampled too.
+    fn sweep(&self, settings: &SolverSettings, domain: AngleDomain<T>) -> Vec<Sample<'_, T>> {
+        let num_points = if settings.adaptive {
+            ADAPTIVE_START_POINTS
+        } else {
+            NUM_POINTS
+        };
+        // Includes the end of the domain, so that the last gap can be filled in too
+        let mut uniform = (0..=num_points).filter_map(|i| {
+            let x = domain.start
+                + domain.length * T::from_double(i as f64) / T::from_double(num_points as f64);
+            self.sample(x)
+        });
+        let Some(mut a) = uniform.next() else {
+            return Vec::new();
+        };
+
+        let tolerance = T::from_double(settings.tolerance);
+        let mut samples = Vec::new();
+        for b in uniform {
+            let mut between = Vec::new();
+            if settings.adaptive {
+                self.refine(&a, &b, tolerance, 0, &mut between);
+            }
+            samples.push(std::mem::replace(&mut a, b));
+            samples.extend(between);
+        }
+        // At the end of a periodic domain, the curves are back where they started
+        if !domain.periodic {
+            samples.push(a);
+        }
+
+        // The best pose is often right up against the end of the allowed angles
+        let boundaries = settings.restrictions.angles.boundaries();
+        if !boundaries.is_empty() {
+            samples.extend(
+                boundaries
+                    .into_iter()
+                    .filter_map(|x| self.sample(T::from_double(x))),
+            );
+            samples.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal));
+            samples.dedup_by(|a, b| a.x == b.x);
+        }
+        samples
+    }
+
+    /// Adds samples between `a` and `b` (in order) until a straight line between each pair
+    /// of neighbours is within `tolerance` of the minimum curve, and any kinks or zero
+    /// crossings are pinned down to within [`KINK_RESOLUTION`].
+    fn refine<'r>(
+        &'r self,
+        a: &Sample<'r, T>,
+        b: &Sample<'r, T>,
+        tolerance: T,
+        depth: u32,
+        out: &mut Vec<Sample<'r, T>>,
+    ) {
+        if depth >= MAX_REFINEMENT_DEPTH {
+            return;
+        }
+        let two = T::from_double(2.0);
+        let Some(mid) = self.sample((a.x + b.x) / two) else {
+            return;
+        };
+
+        let interpolation_error = (mid.min_y - (a.min_y + b.min_y) / two).abs();
+        let kink = a.choice != b.choice;
+        let crossing = (a.min_y > T::zero()) != (b.min_y > T::zero());
+        let wide = b.x - a.x > T::from_double(KINK_RESOLUTION);
+        if interpolation_error > tolerance || ((kink || crossing) && wide) {
+            self.refine(a, &mid, tolerance, depth + 1, out);
+            let mut right = Vec::new();
+            self.refine(&mid, b, tolerance, depth + 1, &mut right);
+            out.push(mid);
+            out.extend(right);
+        }
+    }
+
+    /// The lowest of all the curves at `x`.
+    fn min_value(&self, x: T) -> T {
+        self.index_choices
+            .iter()
+            .map(|index_choice| self.critical_value(index_choice, x))
+            .reduce(T::min)
+            .unwrap_or_else(T::zero)
+    }
+
+    /// Roughly how big the values of the curves can get, for judging how close to zero is
+    /// "close".
+    fn scale(&self) -> T {
+        let max_c = self
+            .planes
+            .iter()
+            .fold(T::zero(), |max, &(_, c)| max.max(c.abs()));
+        let max_b = self
+            .inner_vertices
+            .iter()
+            .fold(T::zero(), |max, b| max.max(b.norm()));
+        self.n_p.sum() * (max_c + max_b)
+    }
+
+    /// Redoes the sums exactly, at the best sampled angle and at a nearby angle where the
+    /// minimum curve peaks, and keeps whichever is better.
+    fn exact_check(
+        &self,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        angle: T,
+    ) -> ExactCheck {
+        let step = T::two_pi() / T::from_double(NUM_POINTS as f64);
+        let refined = self.refine_peak(angle - step, angle + step);
+
+        [angle, refined]
+            .into_iter()
+            .filter_map(|angle| {
+                let angle = angle.to_double();
+                exact_margin_sign(outer_vertices, inner_vertices, angle)
+                    .map(|sign| ExactCheck { angle, sign })
+            })
+            .max_by_key(|check| check.sign)
+            .unwrap_or(ExactCheck {
+                angle: angle.to_double(),
+                sign: Ordering::Less,
+            })
+    }
+
+    /// Narrows in on the peak of the minimum curve between `lo` and `hi` by ternary search.
+    fn refine_peak(&self, mut lo: T, mut hi: T) -> T {
+        let third = T::from_double(1.0 / 3.0);
+        for _ in 0..60 {
+            let m_1 = lo + (hi - lo) * third;
+            let m_2 = hi - (hi - lo) * third;
+            if self.min_value(m_1) < self.min_value(m_2) {
+                lo = m_1;
+            } else {
+                hi = m_2;
+            }
+        }
+        (lo + hi) / T::from_double(2.0)
+    }
+
+    fn fit_at(&self, angle: T, indices: &[usize], translation: Vector2<T>) -> Fit<T> {
+        Fit::Fit {
+            angle,
+            translation,
+            active_constraints: self.active_constraints(indices, angle, translation),
+        }
+    }
+
+    /// The half-planes `(n, g)` that the translation `t` has to stay within, `n.dot(t) <= g`,
+    /// for the inner polygon to fit once it's rotated by `angle`.
+    fn room_constraints(&self, angle: T) -> Vec<(Vector2<T>, T)> {
+        room_constraints(&self.planes, &self.inner_vertices, angle)
+    }
+
+    /// [`Self::room_constraints`], along with any bounds on the translation.
+    fn translation_constraints(&self, angle: T) -> Vec<(Vector2<T>, T)> {
+        let mut constraints = self.room_constraints(angle);
+        constraints.extend_from_slice(&self.bounds);
+        constraints
+    }
+
+    /// The translation (within any bounds) that leaves the rotated inner polygon the most room
+    /// inside the outer one, and the smallest gap it leaves.
+    fn roomiest_pose(&self, angle: T) -> (Vector2<T>, T) {
+        max_min_slack_within(
+            &self.room_constraints(angle),
+            &self.bounds,
+            self.bounds_origin,
+        )
+        .unwrap_or((self.bounds_origin, T::zero()))
+    }
+
+    /// Works out how much room is left between each outer edge and the inner vertex that the
+    /// critical region pairs it with, once the inner polygon is rotated by `angle` and then
+    /// moved by `translation`.
+    fn active_constraints(
+        &self,
+        indices: &[usize],
+        angle: T,
+        translation: Vector2<T>,
+    ) -> Vec<ActiveConstraint<T>> {
+        indices
+            .iter()
+            .zip(&self.planes)
+            .enumerate()
+            .map(|(edge, (&vertex, &plane))| ActiveConstraint {
+                edge,
+                vertex,
+                slack: clearance(plane, self.inner_vertex(vertex), angle)
+                    - plane.0.dot(&translation),
+            })
+            .collect()
+    }
+}
+
+/// Where the straight line through `(x, y)` and `(x_next, y_next)` crosses zero.
+fn zero_crossing<T: Real>(x: T, y: T, x_next: T, y_next: T) -> T {
+    x + (x_next - x) * y / (y - y_next)
+}
+
+/// Finds every run of angles over which `min_ys` is above `threshold`, the least it can be
+/// for B to count as fitting.
+///
+/// `xs` is assumed to be increasing, but not necessarily evenly spaced. If the curve repeats
+/// every `period`, the sample after the last is the first one a period later, so a run
+/// touching both ends of the samples is joined up into a single interval. The ends of each
+/// interval are found by linearly interpolating where the curve crosses `threshold` between
+/// neighbouring samples.
+pub fn feasible_intervals<T: Real>(
+    xs: &[T],
+    min_ys: &[T],
+    period: Option<T>,
+    threshold: T,
+) -> Vec<FeasibleInterval<T>> {
+    let samples = xs.iter().copied().zip(min_ys.iter().copied());
+    let crossing =
+        |x, y, x_next, y_next| zero_crossing(x, y - threshold, x_next, y_next - threshold);
+
+    let mut intervals: Vec<FeasibleInterval<T>> = Vec::new();
+    let mut current: Option<FeasibleInterval<T>> = None;
+    let mut previous: Option<(T, T)> = None;
+    let wrapped_first = period.and_then(|period| {
+        let (x, y) = samples.clone().next()?;
+        Some((x + period, y))
+    });
+    let nexts = samples
+        .clone()
+        .skip(1)
+        .map(Some)
+        .chain(std::iter::once(wrapped_first));
+    for ((x, y), next) in samples.zip(nexts) {
+        if y > threshold {
+            let interval = current.get_or_insert_with(|| FeasibleInterval {
+                start: previous.map_or(x, |(x_prev, y_prev)| crossing(x_prev, y_prev, x, y)),
+                end: x,
+                peak_angle: x,
+                peak_margin: y,
+            });
+            if y > interval.peak_margin {
+                interval.peak_angle = x;
+                interval.peak_margin = y;
+            }
+            interval.end = match next {
+                Some((x_next, y_next)) if y_next > threshold => x_next,
+                Some((x_next, y_next)) => crossing(x, y, x_next, y_next),
+                None => x,
+            };
+        } else if let Some(interval) = current.take() {
+            intervals.push(interval);
+        }
+        previous = Some((x, y));
+    }
+    intervals.extend(current);
+
+    // Join up an interval that runs off the end of the samples with one that starts at the
+    // beginning
+    let wraps = matches!(
+        (min_ys.first(), min_ys.last()),
+        (Some(&first), Some(&last)) if first > threshold && last > threshold
+    );
+    if let Some(period) = period
+        && wraps
+        && intervals.len() > 1
+    {
+        let first = intervals.remove(0);
+        if let Some(last) = intervals.last_mut() {
+            last.end = first.end + period;
+            if first.peak_margin > last.peak_margin {
+                last.peak_angle = first.peak_angle + period;
+                last.peak_margin = first.peak_margin;
+            }
+        }
+    }
+
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{PI, TAU};
+
+    use egui::pos2;
+
+    use super::*;
+    use crate::fit::test_support::{centred, even_sweep, scalene_pair};
+
+    fn equilateral(radius: f32) -> Vec<Pos2> {
+        [90.0_f32, 210.0, 330.0]
+            .into_iter()
+            .map(|degrees| {
+                let (sin, cos) = degrees.to_radians().sin_cos();
+                pos2(radius * cos, radius * sin)
+            })
+            .collect()
+    }
+
+    /// `count` evenly spaced angles over a whole turn, and the cosine of each, which is
+    /// positive either side of 0 (and 2π).
+    fn cosine(count: usize) -> (Vec<f64>, Vec<f64>) {
+        let xs = (0..count)
+            .map(|i| TAU * i as f64 / count as f64)
+            .collect_vec();
+        let ys = xs.iter().map(|x| x.cos()).collect();
+        (xs, ys)
+    }
+
+    #[test]
+    fn no_samples_give_no_intervals() {
+        assert!(feasible_intervals::<f64>(&[], &[], Some(TAU), 0.0).is_empty());
+        assert!(feasible_intervals::<f64>(&[], &[], None, 0.0).is_empty());
+    }
+
+    #[test]
+    fn joins_up_an_interval_that_wraps_past_a_whole_turn() {
+        let (xs, ys) = cosine(360);
+        let [interval] = feasible_intervals(&xs, &ys, Some(TAU), 0.0)[..] else {
+            panic!("expected one interval");
+        };
+        assert!((interval.start - 1.5 * PI).abs() < 1e-3, "{interval:?}");
+        assert!((interval.end - 2.5 * PI).abs() < 1e-3, "{interval:?}");
+        assert!((interval.peak_angle % TAU).abs() < 1e-9, "{interval:?}");
+        assert!((interval.peak_margin - 1.0).abs() < 1e-9, "{interval:?}");
+    }
+
+    #[test]
+    fn leaves_the_ends_apart_without_a_period() {
+        let (xs, ys) = cosine(360);
+        let intervals = feasible_intervals(&xs, &ys, None, 0.0);
+        let ends = intervals
+            .iter()
+            .map(|interval| (interval.start, interval.end))
+            .collect_vec();
+        let [(0.0, first_end), (second_start, last)] = ends[..] else {
+            panic!("expected two intervals, got {ends:?}");
+        };
+        assert!((first_end - 0.5 * PI).abs() < 1e-3);
+        assert!((second_start - 1.5 * PI).abs() < 1e-3);
+        assert_eq!(Some(&last), xs.last());
+    }
+
+    #[test]
+    fn only_counts_angles_above_the_threshold() {
+        let (xs, ys) = cosine(360);
+        let [interval] = feasible_intervals(&xs, &ys, Some(TAU), 0.5)[..] else {
+            panic!("expected one interval");
+        };
+        // cos(x) = 0.5 at ±π/3
+        assert!(
+            (interval.start - 5.0 * PI / 3.0).abs() < 1e-3,
+            "{interval:?}"
+        );
+        assert!((interval.end - 7.0 * PI / 3.0).abs() < 1e-3, "{interval:?}");
+        assert!(feasible_intervals(&xs, &ys, Some(TAU), 1.0).is_empty());
+    }
+
+    #[test]
+    fn has_no_intervals_exactly_when_there_is_no_fit() {
+        let (outer, inner) = scalene_pair();
+        for settings in [even_sweep(), SolverSettings::default()] {
+            for scale in [0.1, 0.5, 0.9, 1.0, 1.05, 1.1, 1.5] {
+                let inner = inner.iter().map(|&v| v * scale).collect_vec();
+                let data = calculate_data(&outer, &inner, &settings);
+                assert_eq!(
+                    matches!(data.fit, Fit::Fit { .. }),
+                    !data.feasible_intervals.is_empty(),
+                    "at {scale}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_fit_shown_by_a_pre_check_still_has_its_angles_and_margin() {
+        let outer = equilateral(60.0);
+        let inner = centred(&[(-5.0, -5.0), (5.0, -5.0), (5.0, 5.0), (-5.0, 5.0)]);
+        let data = calculate_data(&outer, &inner, &SolverSettings::default());
+
+        assert!(matches!(data.fit, Fit::Fit { .. }));
+        assert_eq!(data.decided_by, Some(PreCheck::CircumcircleInIncircle));
+        // B fits at every angle, so the angles that fit are the whole turn
+        let [interval] = data.feasible_intervals.as_slice() else {
+            panic!("{:?}", data.feasible_intervals);
+        };
+        assert!(
+            (interval.end - interval.start - TAU).abs() < 1e-6,
+            "{interval:?}"
+        );
+        let margin = data.margin.expect("B fits");
+        let unchecked = calculate_data(&outer, &inner, &even_sweep());
+        assert!(
+            unchecked
+                .margin
+                .is_some_and(|unchecked| (margin - unchecked).abs() < 1e-3)
+        );
+        assert!(
+            data.max_clearance
+                .is_some_and(|max| (max - margin).abs() < 1e-12)
+        );
+    }
+
+    #[test]
+    fn unfolded_intervals_match_a_full_sweep() {
+        let outer = equilateral(60.0);
+        // With no symmetry of its own, and with a mirror line, so that both a periodic
+        // domain and a mirrored one get unfolded
+        let scalene = centred(&[(8.9, -46.3), (9.0, 42.0), (-17.7, 8.9)]);
+        let isosceles = centred(&[(-40.5, -9.0), (40.5, -9.0), (0.0, 18.0)]);
+        for inner in [scalene, isosceles] {
+            let full = calculate_data(&outer, &inner, &even_sweep());
+            let settings = SolverSettings {
+                use_symmetry: true,
+                ..even_sweep()
+            };
+            let reduced = calculate_data(&outer, &inner, &settings);
+            assert!(reduced.plot_data.domain.length < TAU / 2.0);
+
+            assert_eq!(
+                full.feasible_intervals.len(),
+                reduced.feasible_intervals.len()
+            );
+            for (a, b) in full
+                .feasible_intervals
+                .iter()
+                .zip(&reduced.feasible_intervals)
+            {
+                assert!((a.start - b.start).abs() < 1e-2, "{a:?} vs {b:?}");
+                assert!((a.end - b.end).abs() < 1e-2, "{a:?} vs {b:?}");
+                // The reduced sweep samples more finely, so may find a slightly higher peak
+                let peak_gap = (a.peak_margin - b.peak_margin).abs();
+                assert!(peak_gap < 0.02 * a.peak_margin, "{a:?} vs {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn unfolding_joins_copies_that_meet() {
+        let domain = AngleDomain {
+            start: 0.0,
+            length: TAU / 4.0,
+            periodic: true,
+        };
+        let whole = FeasibleInterval {
+            start: 0.0,
+            end: TAU / 4.0,
+            peak_angle: 0.5,
+            peak_margin: 1.0,
+        };
+        let unfolded = domain.unfold(&[whole]);
+        assert_eq!(unfolded.len(), 1);
+        assert!(
+            unfolded
+                .iter()
+                .all(|i| i.start == 0.0 && (i.end - TAU).abs() < 1e-12)
+        );
+    }
+}
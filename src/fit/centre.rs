@@ -0,0 +1,63 @@
+use itertools::Itertools as _;
+use nalgebra::Vector2;
+
+use super::Real;
+use crate::geometry::clip_to_half_plane;
+
+/// How far out (relative to the biggest offset) the square that [`feasible_region`] starts
+/// from reaches. The region is always bounded when the half-planes come from a polygon, so
+/// this only has to be big enough to hold it.
+const START_SIZE: f64 = 1e6;
+
+/// The corners of the region where `n.dot(t) <= g` for every `(n, g)` in `half_planes`, in
+/// anti-clockwise order. Empty if there's no such region.
+///
+/// This starts from a large square and cuts it down by each half-plane in turn
+/// (Sutherland-Hodgman clipping).
+pub fn feasible_region<T: Real>(half_planes: &[(Vector2<T>, T)]) -> Vec<Vector2<T>> {
+    let size = half_planes
+        .iter()
+        .fold(T::one(), |max, &(_, g)| max.max(g.abs()))
+        * T::from_double(START_SIZE);
+    let mut region = vec![
+        Vector2::new(-size, -size),
+        Vector2::new(size, -size),
+        Vector2::new(size, size),
+        Vector2::new(-size, size),
+    ];
+    for &(n, g) in half_planes {
+        region = clip_to_half_plane(&region, |t| n.dot(&t) - g, |a, b, s| a + (b - a) * s);
+    }
+    region
+}
+
+/// The centroid of the region where `n.dot(t) <= g` for every `(n, g)` in `half_planes`, if
+/// there is one. A region with no area, such as a line or a point, gives the average of its
+/// corners.
+pub fn region_centroid<T: Real>(half_planes: &[(Vector2<T>, T)]) -> Option<Vector2<T>> {
+    let region = feasible_region(half_planes);
+    let num_corners = T::from_double(region.len() as f64);
+    let corner_average = region.iter().sum::<Vector2<T>>() / num_corners;
+
+    // Shoelace formula, measured from the corner average to keep the numbers small
+    let (twice_area, weighted_sum) = region
+        .iter()
+        .map(|v| v - corner_average)
+        .circular_tuple_windows()
+        .fold((T::zero(), Vector2::zeros()), |(area, sum), (a, b)| {
+            let cross = a.x * b.y - b.x * a.y;
+            (area + cross, sum + (a + b) * cross)
+        });
+
+    let scale = region
+        .iter()
+        .fold(T::zero(), |max, v| max.max((v - corner_average).norm()));
+    let degenerate = twice_area.abs() <= T::default_epsilon() * scale * scale;
+    if region.is_empty() {
+        None
+    } else if degenerate {
+        Some(corner_average)
+    } else {
+        Some(corner_average + weighted_sum / (T::from_double(3.0) * twice_area))
+    }
+}
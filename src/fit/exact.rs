@@ -0,0 +1,154 @@
+use std::cmp::Ordering;
+
+use egui::Pos2;
+use itertools::Itertools as _;
+use num_rational::BigRational;
+use num_traits::{One as _, Signed as _, Zero as _};
+
+/// A point or vector with exact rational coordinates.
+#[derive(Clone)]
+struct Exact2 {
+    x: BigRational,
+    y: BigRational,
+}
+
+impl Exact2 {
+    fn from_pos(p: Pos2) -> Option<Self> {
+        Some(Self {
+            x: BigRational::from_float(p.x)?,
+            y: BigRational::from_float(p.y)?,
+        })
+    }
+
+    fn dot(&self, other: &Self) -> BigRational {
+        &self.x * &other.x + &self.y * &other.y
+    }
+
+    fn cross(&self, other: &Self) -> BigRational {
+        &self.x * &other.y - &self.y * &other.x
+    }
+}
+
+/// Works out, in exact arithmetic, whether the inner polygon fits inside the outer one when
+/// rotated by (very nearly) `angle`.
+///
+/// The rotation is made exact by rounding `tan(angle / 2)` to a rational `t` and using
+/// `cos = (1 - t²) / (1 + t²)` and `sin = 2t / (1 + t²)`, so the verdict is exact for the
+/// angle `2 atan(t)`, which differs from `angle` only by floating point round-off. The edge
+/// normals are left unnormalised to avoid square roots, which scales each cosine curve by a
+/// positive amount and so doesn't change the sign of the result.
+///
+/// Returns the sign of the minimum curve, or `None` if the outer polygon isn't a triangle or
+/// any of the numbers involved aren't finite.
+pub fn exact_margin_sign(
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    angle: f64,
+) -> Option<Ordering> {
+    let outer: Vec<Exact2> = outer_vertices
+        .iter()
+        .map(|&p| Exact2::from_pos(p))
+        .collect::<Option<_>>()?;
+    let inner: Vec<Exact2> = inner_vertices
+        .iter()
+        .map(|&p| Exact2::from_pos(p))
+        .collect::<Option<_>>()?;
+
+    // Unnormalised outward normals and offsets, with edge `i` ending at vertex `i`
+    let planes: Vec<(Exact2, BigRational)> = outer
+        .last()
+        .into_iter()
+        .chain(&outer)
+        .tuple_windows()
+        .map(|(from, to)| {
+            let n = Exact2 {
+                x: &to.y - &from.y,
+                y: &from.x - &to.x,
+            };
+            let c = n.dot(to);
+            (n, c)
+        })
+        .collect();
+    let [(n_1, _), (n_2, _), (n_3, _)] = planes.as_slice() else {
+        return None;
+    };
+    let n_p = [n_2.cross(n_3), n_3.cross(n_1), n_1.cross(n_2)];
+
+    let t = BigRational::from_float((angle / 2.0).tan())?;
+    let denominator = BigRational::one() + &t * &t;
+    let cos = (BigRational::one() - &t * &t) / &denominator;
+    let sin = (&t + &t) / &denominator;
+
+    // Because every `n_p` is non-negative, the minimum over all the index choices splits
+    // into a minimum over `j` for each edge separately
+    let margin = planes
+        .iter()
+        .zip(&n_p)
+        .map(|((n, c), n_p_i)| {
+            let min_clearance = inner
+                .iter()
+                .map(|b| c - (n.dot(b) * &cos + n.cross(b) * &sin))
+                .min()?;
+            Some(n_p_i * min_clearance)
+        })
+        .sum::<Option<BigRational>>()?;
+
+    Some(if margin.is_zero() {
+        Ordering::Equal
+    } else if margin.is_positive() {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fit::{
+        calculate_data,
+        test_support::{even_sweep, scalene_pair},
+    };
+
+    #[test]
+    fn agrees_with_the_minimum_curve_at_every_angle() {
+        let (outer, inner) = scalene_pair();
+        let data = calculate_data(&outer, &inner, &even_sweep());
+        let samples = data
+            .plot_data
+            .xs
+            .iter()
+            .zip(&data.plot_data.min_ys)
+            // Right by a crossing, round-off in the float curve could go either way
+            .filter(|&(_, y)| y.abs() > 1e-3)
+            .collect_vec();
+        assert!(
+            samples.iter().any(|&(_, &y)| y > 0.0) && samples.iter().any(|&(_, &y)| y < 0.0),
+            "B should fit at some angles and not others"
+        );
+
+        for (&angle, &y) in samples {
+            let expected = if y > 0.0 {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+            assert_eq!(
+                exact_margin_sign(&outer, &inner, angle),
+                Some(expected),
+                "at angle {angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn needs_a_triangle() {
+        let square = [
+            Pos2::new(-1.0, -1.0),
+            Pos2::new(1.0, -1.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(-1.0, 1.0),
+        ];
+        assert_eq!(exact_margin_sign(&square, &square, 0.0), None);
+    }
+}
@@ -0,0 +1,308 @@
+use std::f32::consts::TAU;
+
+use egui::{Pos2, Vec2};
+
+use super::{Fit, PolygonDistribution, SolverKind, SolverSettings, solver::solve_orientations};
+use crate::geometry::{diameter, is_convex, min_width, perimeter, signed_area, vertex_centroid};
+
+/// How many of the best finds are kept.
+const MAX_FINDS: usize = 10;
+
+/// How far a vertex is moved at first when hill climbing, as a fraction of the size of its
+/// polygon.
+const INITIAL_STEP: f32 = 0.1;
+
+/// The step below which a climb is given up on, and a new one started from a fresh pair.
+const MIN_STEP: f32 = 1e-3;
+
+/// How much room B has to have left, as a fraction of A's diameter, to count as fitting.
+/// Climbs end up right against the edge of what fits, and without this the finds there
+/// would hinge on the solver's round-off.
+const MIN_MARGIN: f64 = 1e-3;
+
+/// How many moves in a row can fail to improve on the climb before the step is halved.
+const FAILURES_BEFORE_SHRINKING: usize = 20;
+
+/// What makes a fitting pair interesting, as a score to be made as big as possible.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum SearchCriterion {
+    /// The area of B as a fraction of the area of A.
+    #[default]
+    AreaRatio,
+    /// B's diameter over A's narrowest width, which is surprising when it's more than one.
+    DiameterOverWidth,
+    /// The perimeter of B as a fraction of the perimeter of A.
+    PerimeterRatio,
+}
+
+impl SearchCriterion {
+    pub const ALL: [Self; 3] = [
+        Self::AreaRatio,
+        Self::DiameterOverWidth,
+        Self::PerimeterRatio,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AreaRatio => "Area ratio",
+            Self::DiameterOverWidth => "Diameter over width",
+            Self::PerimeterRatio => "Perimeter ratio",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::AreaRatio => "Make B's area as large a fraction of A's as possible",
+            Self::DiameterOverWidth => {
+                "Make B's diameter as long as possible next to A's narrowest width"
+            }
+            Self::PerimeterRatio => "Make B's perimeter as large a fraction of A's as possible",
+        }
+    }
+
+    /// How well the pair scores, whether or not B fits.
+    pub fn score(self, outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> f64 {
+        let ratio = |inner: f32, outer: f32| f64::from(inner) / f64::from(outer);
+        match self {
+            Self::AreaRatio => ratio(signed_area(inner_vertices), signed_area(outer_vertices)),
+            Self::DiameterOverWidth => ratio(diameter(inner_vertices), min_width(outer_vertices).0),
+            Self::PerimeterRatio => ratio(perimeter(inner_vertices), perimeter(outer_vertices)),
+        }
+    }
+}
+
+/// How the [`Explorer`] comes up with pairs to try.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum SearchStrategy {
+    /// Draw every pair afresh.
+    Random,
+    /// Start from a random pair that fits, and keep nudging its vertices while that improves
+    /// the score.
+    #[default]
+    HillClimb,
+}
+
+impl SearchStrategy {
+    pub const ALL: [Self; 2] = [Self::Random, Self::HillClimb];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "Random",
+            Self::HillClimb => "Hill climbing",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Random => "Draw every pair afresh",
+            Self::HillClimb => {
+                "Nudge the vertices of a pair that fits for as long as that improves the score"
+            }
+        }
+    }
+}
+
+/// A pair in which B fits, and how well it scored.
+#[derive(Clone, Debug)]
+pub struct Find {
+    /// Both polygons, centred on their centroids.
+    pub outer_vertices: Vec<Pos2>,
+    pub inner_vertices: Vec<Pos2>,
+    pub score: f64,
+    pub margin: Option<f64>,
+    /// Which climb (or random draw) this came from, so that each only has one place in the
+    /// list.
+    lineage: usize,
+}
+
+/// The pair a hill climb has got to, and how big its moves are.
+struct Climb {
+    best: Find,
+    step: f32,
+    failures: usize,
+}
+
+/// Searches over pairs of polygons for ones where B fits and the [`SearchCriterion`] scores
+/// as highly as possible, a few pairs at a time so that it can be spread over several
+/// frames.
+///
+/// Every pair is checked with the same solver as the editor, and the best few are kept.
+pub struct Explorer {
+    criterion: SearchCriterion,
+    strategy: SearchStrategy,
+    outer: PolygonDistribution,
+    inner: PolygonDistribution,
+    rng: fastrand::Rng,
+    solver: SolverKind,
+    settings: SolverSettings,
+    num_evaluations: usize,
+    num_lineages: usize,
+    climb: Option<Climb>,
+    /// The best finds so far, best first.
+    finds: Vec<Find>,
+}
+
+impl Explorer {
+    /// A search drawing A from `outer` and B from `inner` to start from.
+    ///
+    /// The pre-checks are skipped, as those that rule B out don't give a margin, and so is
+    /// the proof.
+    pub fn new(
+        criterion: SearchCriterion,
+        strategy: SearchStrategy,
+        outer: PolygonDistribution,
+        inner: PolygonDistribution,
+        seed: u64,
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Self {
+        Self {
+            criterion,
+            strategy,
+            outer,
+            inner,
+            rng: fastrand::Rng::with_seed(seed),
+            solver,
+            settings: SolverSettings {
+                pre_checks: false,
+                verify: false,
+                ..settings.clone()
+            },
+            num_evaluations: 0,
+            num_lineages: 0,
+            climb: None,
+            finds: Vec::new(),
+        }
+    }
+
+    pub fn criterion(&self) -> SearchCriterion {
+        self.criterion
+    }
+
+    pub fn solver(&self) -> SolverKind {
+        self.solver
+    }
+
+    /// How many pairs have been solved so far.
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations
+    }
+
+    /// The score of the pair the current hill climb has got to, if one is under way.
+    pub fn climb_score(&self) -> Option<f64> {
+        self.climb.as_ref().map(|climb| climb.best.score)
+    }
+
+    /// The best finds so far, best first.
+    pub fn finds(&self) -> &[Find] {
+        &self.finds
+    }
+
+    /// Tries one more pair.
+    pub fn run_next(&mut self) {
+        match (self.strategy, self.climb.take()) {
+            (SearchStrategy::HillClimb, Some(climb)) => self.climb = self.climb_further(climb),
+            (SearchStrategy::Random, _) | (SearchStrategy::HillClimb, None) => {
+                let pair = self
+                    .outer
+                    .sample(&mut self.rng)
+                    .zip(self.inner.sample(&mut self.rng));
+                let Some((outer_vertices, inner_vertices)) = pair else {
+                    return;
+                };
+                let Some(find) = self.evaluate(outer_vertices, inner_vertices) else {
+                    return;
+                };
+                let find = Find {
+                    lineage: self.num_lineages,
+                    ..find
+                };
+                self.num_lineages += 1;
+                self.record(&find);
+                if self.strategy == SearchStrategy::HillClimb {
+                    self.climb = Some(Climb {
+                        best: find,
+                        step: INITIAL_STEP,
+                        failures: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Nudges one vertex of the climb's B, keeping the move if B still fits and the score is
+    /// no worse. Returns the climb as it's left, or `None` once it's been given up on.
+    ///
+    /// A is left as it was drawn, as otherwise the climb makes for slivers of A, where the
+    /// scores that are ratios to A's width run away.
+    fn climb_further(&mut self, mut climb: Climb) -> Option<Climb> {
+        let mut inner_vertices = climb.best.inner_vertices.clone();
+        let reach = climb.step * diameter(&inner_vertices);
+        let i = self.rng.usize(..inner_vertices.len());
+        if let Some(vertex) = inner_vertices.get_mut(i) {
+            *vertex += reach * self.rng.f32() * Vec2::angled(self.rng.f32() * TAU);
+        }
+
+        let better = (is_convex(&inner_vertices) && signed_area(&inner_vertices) > 0.0)
+            .then(|| {
+                let outer_vertices = climb.best.outer_vertices.clone();
+                self.evaluate(outer_vertices, recentred(inner_vertices))
+            })
+            .flatten()
+            .filter(|find| find.score >= climb.best.score);
+        if let Some(find) = better {
+            climb.best = Find {
+                lineage: climb.best.lineage,
+                ..find
+            };
+            climb.failures = 0;
+            self.record(&climb.best);
+            return Some(climb);
+        }
+
+        climb.failures += 1;
+        if climb.failures >= FAILURES_BEFORE_SHRINKING {
+            climb.step /= 2.0;
+            climb.failures = 0;
+        }
+        (climb.step >= MIN_STEP).then_some(climb)
+    }
+
+    /// Solves the pair, and scores it if B fits with at least [`MIN_MARGIN`] to spare.
+    fn evaluate(&mut self, outer_vertices: Vec<Pos2>, inner_vertices: Vec<Pos2>) -> Option<Find> {
+        self.num_evaluations += 1;
+        let data = solve_orientations(
+            self.solver.solver(),
+            &outer_vertices,
+            &inner_vertices,
+            &self.settings,
+        );
+        let min_margin = MIN_MARGIN * f64::from(diameter(&outer_vertices));
+        if !matches!(data.fit, Fit::Fit { .. }) || data.margin.is_none_or(|m| m < min_margin) {
+            return None;
+        }
+        Some(Find {
+            score: self.criterion.score(&outer_vertices, &inner_vertices),
+            outer_vertices,
+            inner_vertices,
+            margin: data.margin,
+            lineage: 0,
+        })
+    }
+
+    /// Keeps `find` if it's among the best, in place of any earlier find from the same
+    /// lineage.
+    fn record(&mut self, find: &Find) {
+        self.finds.retain(|kept| kept.lineage != find.lineage);
+        let index = self.finds.partition_point(|kept| kept.score >= find.score);
+        if index < MAX_FINDS {
+            self.finds.insert(index, find.clone());
+            self.finds.truncate(MAX_FINDS);
+        }
+    }
+}
+
+fn recentred(vertices: Vec<Pos2>) -> Vec<Pos2> {
+    let centroid = vertex_centroid(&vertices).to_vec2();
+    vertices.into_iter().map(|v| v - centroid).collect()
+}
@@ -0,0 +1,161 @@
+use egui::Pos2;
+
+use super::{
+    SolverKind, SolverSettings, SweepOutcome, SweepParameter, parallel::in_parallel, solver::solve,
+};
+
+/// How B fared in one cell of a [`FeasibilityMap`].
+#[derive(Clone, Copy, Debug)]
+pub struct MapCell {
+    /// `None` if there was nothing solved here, or there was no margin to give.
+    pub margin: Option<f64>,
+    pub outcome: SweepOutcome,
+}
+
+/// One of the two parameters of a [`FeasibilityMap`], split into cells.
+#[derive(Clone, Copy, Debug)]
+pub struct MapAxis {
+    pub parameter: SweepParameter,
+    pub range: (f64, f64),
+    /// How many cells the range is split into.
+    pub resolution: usize,
+}
+
+impl MapAxis {
+    pub fn new(parameter: SweepParameter, range: (f64, f64), resolution: usize) -> Self {
+        Self {
+            parameter,
+            range,
+            resolution: resolution.max(1),
+        }
+    }
+
+    /// How wide each cell is.
+    pub fn cell_size(&self) -> f64 {
+        (self.range.1 - self.range.0) / self.resolution as f64
+    }
+
+    /// The value of the parameter at the middle of cell `index`.
+    pub fn value(&self, index: usize) -> f64 {
+        self.range.0 + self.cell_size() * (index as f64 + 0.5)
+    }
+
+    /// The cell that `value` falls in, if it's within the range.
+    pub fn index_of(&self, value: f64) -> Option<usize> {
+        let index = ((value - self.range.0) / self.cell_size()).floor();
+        (index >= 0.0 && index < self.resolution as f64).then_some(index as usize)
+    }
+}
+
+/// Runs the solver over a grid of values of two [`SweepParameter`]s, a row at a time so that
+/// it can be spread over several frames.
+///
+/// The cells in each row are solved in parallel, where threads are available.
+pub struct FeasibilityMap {
+    x: MapAxis,
+    y: MapAxis,
+    outer_vertices: Vec<Pos2>,
+    inner_vertices: Vec<Pos2>,
+    solver: SolverKind,
+    settings: SolverSettings,
+    /// The cells solved so far, a row along `x` for each value of `y` in turn.
+    cells: Vec<MapCell>,
+}
+
+impl FeasibilityMap {
+    /// A map of `x` across by `y` up, starting from the polygons as they are, which should be
+    /// centred on their centroids.
+    ///
+    /// The pre-checks are skipped, as those that rule B out don't give a margin, and so is
+    /// the proof.
+    pub fn new(
+        x: MapAxis,
+        y: MapAxis,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            outer_vertices: outer_vertices.to_vec(),
+            inner_vertices: inner_vertices.to_vec(),
+            solver,
+            settings: SolverSettings {
+                pre_checks: false,
+                verify: false,
+                ..settings.clone()
+            },
+            cells: Vec::new(),
+        }
+    }
+
+    pub fn x(&self) -> &MapAxis {
+        &self.x
+    }
+
+    pub fn y(&self) -> &MapAxis {
+        &self.y
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cells.len() >= self.x.resolution * self.y.resolution
+    }
+
+    /// How far through the map is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.cells.len() as f32 / (self.x.resolution * self.y.resolution) as f32
+    }
+
+    /// The cell in column `i` and row `j`, if it's been solved yet.
+    pub fn cell(&self, i: usize, j: usize) -> Option<MapCell> {
+        if i >= self.x.resolution {
+            return None;
+        }
+        self.cells.get(j * self.x.resolution + i).copied()
+    }
+
+    /// The cell holding the point `(x, y)` in parameter space, if it's on the map.
+    pub fn cell_at(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        Some((self.x.index_of(x)?, self.y.index_of(y)?))
+    }
+
+    /// Both polygons, centred on their centroids, as they are in cell `(i, j)`, or `None` if
+    /// they aren't convex there.
+    pub fn configuration(&self, i: usize, j: usize) -> Option<(Vec<Pos2>, Vec<Pos2>)> {
+        let (outer, inner) =
+            self.x
+                .parameter
+                .apply(self.x.value(i), &self.outer_vertices, &self.inner_vertices)?;
+        self.y.parameter.apply(self.y.value(j), &outer, &inner)
+    }
+
+    /// Solves the next row of the map.
+    pub fn run_next_row(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+
+        let j = self.cells.len() / self.x.resolution;
+        let columns: Vec<usize> = (0..self.x.resolution).collect();
+        let row = in_parallel(&columns, |&i| self.solve_cell(i, j));
+        self.cells.extend(row);
+    }
+
+    fn solve_cell(&self, i: usize, j: usize) -> MapCell {
+        self.configuration(i, j).map_or(
+            MapCell {
+                margin: None,
+                outcome: SweepOutcome::NotConvex,
+            },
+            |(outer, inner)| {
+                let data = solve(self.solver.solver(), &outer, &inner, &self.settings);
+                MapCell {
+                    margin: data.margin,
+                    outcome: SweepOutcome::of(&data.fit),
+                }
+            },
+        )
+    }
+}
@@ -0,0 +1,252 @@
+use nalgebra::{DMatrix, Vector2};
+
+use super::Real;
+
+/// The columns of the tableau before the slack variables: the positive and negative parts
+/// of `t.x` and `t.y`, then `s` (less its starting value).
+const NUM_VARIABLES: usize = 5;
+
+/// How many pivots to allow before giving up. Bland's rule can't cycle, so this is only a
+/// backstop against round-off.
+const MAX_PIVOTS: usize = 1000;
+
+/// Finds the translation `t` that leaves the most room `s` inside a set of half-planes, by
+/// maximising `s` subject to `n.dot(t) + s <= g` for each `(n, g)` in `constraints`.
+///
+/// This is a small linear program in `(t.x, t.y, s)`, solved with the simplex method (using
+/// Bland's rule to choose pivots). Starting `s` off at the smallest `g` means `t = 0` is
+/// always a feasible place to begin. Returns `None` if there are no constraints, or if they
+/// don't stop `s` growing without limit.
+pub fn max_min_slack<T: Real>(constraints: &[(Vector2<T>, T)]) -> Option<(Vector2<T>, T)> {
+    max_min_slack_within(constraints, &[], Vector2::zeros())
+}
+
+/// [`max_min_slack`], but with `t` also kept within the half-planes `n.dot(t) <= c` for each
+/// `(n, c)` in `bounds`, which don't need to leave any room.
+///
+/// The search starts from `origin`, which has to satisfy all of the `bounds`. Returns `None`
+/// if it doesn't.
+pub fn max_min_slack_within<T: Real>(
+    constraints: &[(Vector2<T>, T)],
+    bounds: &[(Vector2<T>, T)],
+    origin: Vector2<T>,
+) -> Option<(Vector2<T>, T)> {
+    // Measure `t` from the origin, so that the search can start at zero
+    let shift = |&(n, g): &(Vector2<T>, T)| (n, g - n.dot(&origin));
+    let constraints = constraints.iter().map(shift).collect::<Vec<_>>();
+    let bounds = bounds.iter().map(shift).collect::<Vec<_>>();
+    if bounds.iter().any(|&(_, c)| c < T::zero()) {
+        return None;
+    }
+
+    let num_rows = constraints.len() + bounds.len();
+    let offset = constraints.iter().map(|&(_, g)| g).reduce(T::min)?;
+    let num_cols = NUM_VARIABLES + num_rows + 1;
+    let rhs = num_cols - 1;
+    let epsilon = T::default_epsilon() * T::from_double(1000.0);
+
+    let unit = |i: usize, k: usize| if i == k { T::one() } else { T::zero() };
+    // The bounds don't involve `s`, and don't need shifting by the offset either
+    let rows = constraints
+        .iter()
+        .map(|&(n, g)| (n, T::one(), g - offset))
+        .chain(bounds.iter().map(|&(n, c)| (n, T::zero(), c)));
+    let constraint_rows = rows.enumerate().flat_map(|(k, (n, s, g))| {
+        [n.x, -n.x, n.y, -n.y, s]
+            .into_iter()
+            .chain((0..num_rows).map(move |i| unit(i, k)))
+            .chain([g])
+    });
+    // We're maximising `s`, so the objective row starts off as `-s`
+    let objective_row = (0..num_cols).map(|col| {
+        if col == NUM_VARIABLES - 1 {
+            -T::one()
+        } else {
+            T::zero()
+        }
+    });
+    let mut tableau =
+        DMatrix::from_row_iterator(num_rows + 1, num_cols, constraint_rows.chain(objective_row));
+    let at = |tableau: &DMatrix<T>, row: usize, col: usize| {
+        tableau.get((row, col)).copied().unwrap_or_else(T::zero)
+    };
+
+    // Which variable each constraint row currently solves for: the slacks, to begin with
+    let mut basis: Vec<usize> = (NUM_VARIABLES..NUM_VARIABLES + num_rows).collect();
+    for _ in 0..MAX_PIVOTS {
+        // Bland's rule: the first column that would improve the objective enters...
+        let Some(entering) = (0..rhs).find(|&col| at(&tableau, num_rows, col) < -epsilon) else {
+            let value = |variable: usize| {
+                basis
+                    .iter()
+                    .position(|&basic| basic == variable)
+                    .map_or_else(T::zero, |row| at(&tableau, row, rhs))
+            };
+            let translation = origin + Vector2::new(value(0) - value(1), value(2) - value(3));
+            return Some((translation, value(4) + offset));
+        };
+
+        // ...and the row that limits it the most leaves, breaking ties by the lowest index
+        let (leaving, _) = basis
+            .iter()
+            .enumerate()
+            .filter_map(|(row, &basic)| {
+                let a = at(&tableau, row, entering);
+                (a > epsilon).then(|| (row, (at(&tableau, row, rhs) / a, basic)))
+            })
+            .min_by(|(_, (ratio_a, basic_a)), (_, (ratio_b, basic_b))| {
+                ratio_a
+                    .partial_cmp(ratio_b)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(basic_a.cmp(basic_b))
+            })?;
+
+        pivot(&mut tableau, leaving, entering);
+        if let Some(basic) = basis.get_mut(leaving) {
+            *basic = entering;
+        }
+    }
+
+    None
+}
+
+/// Rescales row `row` so that it has a 1 in column `col`, then clears that column from every
+/// other row.
+fn pivot<T: Real>(tableau: &mut DMatrix<T>, row: usize, col: usize) {
+    let Some(&pivot) = tableau.get((row, col)) else {
+        return;
+    };
+    let pivot_row = tableau.row(row).clone_owned() / pivot;
+    tableau.set_row(row, &pivot_row);
+
+    for other in (0..tableau.nrows()).filter(|&other| other != row) {
+        let Some(&factor) = tableau.get((other, col)) else {
+            continue;
+        };
+        let reduced = tableau.row(other) - &pivot_row * factor;
+        tableau.set_row(other, &reduced);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{SQRT_2, TAU};
+
+    use nalgebra::vector;
+
+    use super::*;
+    use crate::fit::{
+        CriticalRegion, half_planes, room_constraints,
+        test_support::{centred, scalene_pair},
+        to_vectors,
+    };
+
+    /// The box `[0, width] × [0, height]`, as half-planes with unit normals.
+    fn rectangle(width: f64, height: f64) -> Vec<(Vector2<f64>, f64)> {
+        vec![
+            (vector![1.0, 0.0], width),
+            (vector![-1.0, 0.0], 0.0),
+            (vector![0.0, 1.0], height),
+            (vector![0.0, -1.0], 0.0),
+        ]
+    }
+
+    #[test]
+    fn finds_the_incentre_of_a_triangle() {
+        // The right triangle with corners at the origin, (1, 0) and (0, 1)
+        let constraints = [
+            (vector![-1.0, 0.0], 0.0),
+            (vector![0.0, -1.0], 0.0),
+            (vector![1.0, 1.0] / SQRT_2, 1.0 / SQRT_2),
+        ];
+        let (t, s) = max_min_slack(&constraints).expect("the triangle is bounded");
+        let inradius = (2.0 - SQRT_2) / 2.0;
+        assert!((s - inradius).abs() < 1e-12, "{s}");
+        assert!((t - vector![inradius, inradius]).norm() < 1e-12, "{t}");
+    }
+
+    #[test]
+    fn finds_the_most_room_across_a_rectangle() {
+        let (t, s) = max_min_slack(&rectangle(4.0, 2.0)).expect("the box is bounded");
+        // Anywhere along the middle line, away from the short sides, is as good as any other
+        assert!((s - 1.0).abs() < 1e-12, "{s}");
+        assert!(
+            (t.y - 1.0).abs() < 1e-12 && (1.0..=3.0).contains(&t.x),
+            "{t}"
+        );
+    }
+
+    #[test]
+    fn gives_up_without_a_bounded_optimum() {
+        assert!(max_min_slack::<f64>(&[]).is_none());
+        // Moving ever further left leaves ever more room
+        assert!(max_min_slack(&[(vector![1.0, 0.0], 1.0)]).is_none());
+    }
+
+    #[test]
+    fn rejects_an_origin_outside_the_bounds() {
+        let bounds = [(vector![1.0, 0.0], -1.0)];
+        assert!(max_min_slack_within(&rectangle(4.0, 2.0), &bounds, Vector2::zeros()).is_none());
+    }
+
+    #[test]
+    fn keeps_within_the_bounds() {
+        // B's centroid has to stay left of x = 0.5, so can't get to the middle of the box
+        let bounds = [(vector![1.0, 0.0], 0.5)];
+        let (t, s) = max_min_slack_within(&rectangle(4.0, 2.0), &bounds, vector![0.25, 0.5])
+            .expect("the origin is within the bounds");
+        assert!((s - 0.5).abs() < 1e-12, "{s}");
+        assert!((t.x - 0.5).abs() < 1e-12, "{t}");
+    }
+
+    #[test]
+    fn copes_with_degenerate_pivots() {
+        // Every side of the square three times over, and diagonals that are tight at the
+        // optimum too, so that the ratio test ties over and over again
+        let square = rectangle(2.0, 2.0);
+        let diagonals = [
+            (vector![1.0, 1.0] / SQRT_2, SQRT_2 + 1.0),
+            (vector![-1.0, -1.0] / SQRT_2, 1.0 - SQRT_2),
+        ];
+        let constraints = square
+            .iter()
+            .chain(&square)
+            .chain(&square)
+            .chain(&diagonals)
+            .copied()
+            .collect::<Vec<_>>();
+        let (t, s) = max_min_slack(&constraints).expect("the square is bounded");
+        let (t_alone, s_alone) = max_min_slack(&square).expect("the square is bounded");
+        assert!((s - s_alone).abs() < 1e-12, "{s} vs {s_alone}");
+        assert!((t - t_alone).norm() < 1e-12, "{t} vs {t_alone}");
+    }
+
+    #[test]
+    fn agrees_with_the_critical_region() {
+        let (outer, triangle) = scalene_pair();
+        let pentagon = centred(&[
+            (-20.0, -12.0),
+            (15.0, -15.0),
+            (22.0, 6.0),
+            (0.0, 20.0),
+            (-18.0, 8.0),
+        ]);
+        let planes = half_planes(&to_vectors::<f64>(&outer));
+        for inner in [triangle, pentagon] {
+            let inner = to_vectors(&inner);
+            let region =
+                CriticalRegion::new(planes.clone(), inner.clone()).expect("A is a triangle");
+            for i in 0..64 {
+                let angle = TAU * f64::from(i) / 64.0;
+                let sample = region.sample(angle).expect("there are critical planes");
+                let expected = sample.min_y / region.n_p.sum();
+                let (_, s) =
+                    max_min_slack(&room_constraints(&planes, &inner, angle)).expect("A is bounded");
+                assert!(
+                    (s - expected).abs() < 1e-9 * region.scale(),
+                    "{s} vs {expected} at {angle}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::f32::consts::TAU;
+
+use egui::{Pos2, Vec2};
+
+use super::{Fit, SolverKind, SolverSettings, parallel::in_parallel, solver::solve_orientations};
+use crate::geometry::{convex_hull, is_convex, vertex_centroid};
+
+/// How many times a sample that comes out degenerate (or not convex) is drawn again before
+/// giving up on it.
+const MAX_ATTEMPTS: usize = 100;
+
+/// A way of drawing random convex polygons.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum PolygonFamily {
+    /// Points at random angles around a circle.
+    #[default]
+    OnCircle,
+    /// The convex hull of points scattered at random over a disc.
+    RandomHull,
+    /// A regular polygon with each vertex knocked a little way off.
+    PerturbedRegular,
+}
+
+impl PolygonFamily {
+    pub const ALL: [Self; 3] = [Self::OnCircle, Self::RandomHull, Self::PerturbedRegular];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OnCircle => "On a circle",
+            Self::RandomHull => "Random hull",
+            Self::PerturbedRegular => "Perturbed regular",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::OnCircle => "Vertices at random angles around a circle",
+            Self::RandomHull => {
+                "The convex hull of points scattered over a disc, so it may have fewer vertices"
+            }
+            Self::PerturbedRegular => "A regular polygon with each vertex moved a random amount",
+        }
+    }
+}
+
+/// The family a random polygon is drawn from, and how big it is.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PolygonDistribution {
+    pub family: PolygonFamily,
+    /// The number of vertices, or of points to take the hull of.
+    pub num_vertices: usize,
+    /// The radius of the circle or disc the vertices are drawn on or from.
+    pub radius: f32,
+    /// How far each vertex of a perturbed regular polygon can move, as a fraction of the
+    /// radius.
+    pub jitter: f32,
+}
+
+impl PolygonDistribution {
+    pub fn new(family: PolygonFamily, num_vertices: usize, radius: f32) -> Self {
+        Self {
+            family,
+            num_vertices,
+            radius,
+            jitter: 0.2,
+        }
+    }
+
+    /// A random convex polygon, with its vertices defined anti-clockwise and centred on
+    /// their centroid. `None` if none of the attempts at one came out convex with at least
+    /// three vertices.
+    pub fn sample(&self, rng: &mut fastrand::Rng) -> Option<Vec<Pos2>> {
+        let num_vertices = self.num_vertices.max(3);
+        let on_circle = |angle: f32| self.radius * Vec2::angled(angle);
+        (0..MAX_ATTEMPTS).find_map(|_| {
+            let vertices = match self.family {
+                PolygonFamily::OnCircle => {
+                    let mut angles: Vec<f32> = (0..num_vertices).map(|_| rng.f32() * TAU).collect();
+                    angles.sort_by(f32::total_cmp);
+                    angles.into_iter().map(|a| on_circle(a).to_pos2()).collect()
+                }
+                PolygonFamily::RandomHull => {
+                    // The square root spreads the points evenly over the area of the disc
+                    let points: Vec<Pos2> = (0..num_vertices)
+                        .map(|_| (on_circle(rng.f32() * TAU) * rng.f32().sqrt()).to_pos2())
+                        .collect();
+                    convex_hull(&points)
+                }
+                PolygonFamily::PerturbedRegular => (0..num_vertices)
+                    .map(|i| {
+                        let offset = on_circle(rng.f32() * TAU) * self.jitter * rng.f32().sqrt();
+                        (on_circle(i as f32 * TAU / num_vertices as f32) + offset).to_pos2()
+                    })
+                    .collect(),
+            };
+            let centroid = vertex_centroid(&vertices).to_vec2();
+            let vertices: Vec<Pos2> = vertices.into_iter().map(|v| v - centroid).collect();
+            (vertices.len() >= 3 && is_convex(&vertices)).then_some(vertices)
+        })
+    }
+}
+
+/// One random pair of polygons, and how B fared in A.
+#[derive(Clone, Debug)]
+pub struct TrialPair {
+    pub outer_vertices: Vec<Pos2>,
+    pub inner_vertices: Vec<Pos2>,
+    pub margin: Option<f64>,
+    pub fits: bool,
+}
+
+/// Draws random pairs of polygons and solves each, a batch at a time so that it can be spread
+/// over several frames, to estimate how likely it is that B fits in A.
+///
+/// The pairs are all drawn from one generator in turn, so the same seed always gives the
+/// same pairs. Each batch is then solved in parallel, where threads are available.
+pub struct RandomExperiment {
+    outer: PolygonDistribution,
+    inner: PolygonDistribution,
+    num_trials: usize,
+    rng: fastrand::Rng,
+    solver: SolverKind,
+    settings: SolverSettings,
+    trials: Vec<TrialPair>,
+}
+
+impl RandomExperiment {
+    /// An experiment of `num_trials` pairs, with A drawn from `outer` and B from `inner`.
+    ///
+    /// The pre-checks are skipped, as those that rule B out don't give a margin, and so is
+    /// the proof.
+    pub fn new(
+        outer: PolygonDistribution,
+        inner: PolygonDistribution,
+        num_trials: usize,
+        seed: u64,
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Self {
+        Self {
+            outer,
+            inner,
+            num_trials,
+            rng: fastrand::Rng::with_seed(seed),
+            solver,
+            settings: SolverSettings {
+                pre_checks: false,
+                verify: false,
+                ..settings.clone()
+            },
+            trials: Vec::new(),
+        }
+    }
+
+    /// The pairs drawn so far, in the order they were drawn.
+    pub fn trials(&self) -> &[TrialPair] {
+        &self.trials
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.trials.len() >= self.num_trials
+    }
+
+    /// How far through the experiment is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.trials.len() as f32 / self.num_trials.max(1) as f32
+    }
+
+    /// The fraction of the pairs so far in which B fits, and its standard error. `None`
+    /// before any have been drawn.
+    pub fn fit_fraction(&self) -> Option<(f64, f64)> {
+        let n = self.trials.len() as f64;
+        if n == 0.0 {
+            return None;
+        }
+        let fraction = self.trials.iter().filter(|trial| trial.fits).count() as f64 / n;
+        Some((fraction, (fraction * (1.0 - fraction) / n).sqrt()))
+    }
+
+    /// Draws and solves up to `batch_size` more pairs.
+    pub fn run_batch(&mut self, batch_size: usize) {
+        let batch_size = batch_size.min(self.num_trials.saturating_sub(self.trials.len()));
+        let pairs: Vec<Option<(Vec<Pos2>, Vec<Pos2>)>> = (0..batch_size)
+            .map(|_| {
+                let outer = self.outer.sample(&mut self.rng);
+                let inner = self.inner.sample(&mut self.rng);
+                outer.zip(inner)
+            })
+            .collect();
+        let (solver, settings) = (self.solver, &self.settings);
+        let trials = in_parallel(&pairs, |pair| {
+            let (outer_vertices, inner_vertices) = pair.clone()?;
+            let data =
+                solve_orientations(solver.solver(), &outer_vertices, &inner_vertices, settings);
+            Some(TrialPair {
+                outer_vertices,
+                inner_vertices,
+                margin: data.margin,
+                fits: matches!(data.fit, Fit::Fit { .. }),
+            })
+        });
+        // Pairs that couldn't be drawn at all are left out
+        self.trials.extend(trials.into_iter().flatten());
+        if batch_size > 0 && pairs.iter().all(Option::is_none) {
+            // Nothing can be drawn with these settings, so stop rather than trying forever
+            self.num_trials = self.trials.len();
+        }
+    }
+}
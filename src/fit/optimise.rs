@@ -0,0 +1,382 @@
+use egui::{Pos2, Vec2};
+use itertools::Itertools as _;
+use nalgebra::{DMatrix, DVector};
+
+use super::{Fit, FitSolver, SolverSettings, solver::solve_orientations};
+use crate::geometry::{diameter, edges, is_convex, signed_area, vertex_centroid};
+
+/// How far the first moves go, relative to the size of B.
+const START_STEP: f32 = 0.05;
+
+/// The optimiser stops once its moves are this small, relative to the size of B.
+const MIN_STEP: f32 = 1e-4;
+
+/// How closely a shape has to keep to the constraints, relative to the size of B.
+const CONSTRAINT_TOLERANCE: f64 = 1e-4;
+
+/// How many times the constraints are each put right in turn, when they can't be put right
+/// all at once.
+const CONSTRAINT_ROUNDS: usize = 20;
+
+/// What the optimiser makes as big as it can.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ShapeObjective {
+    #[default]
+    Area,
+    /// The longest distance across B.
+    Scale,
+}
+
+impl ShapeObjective {
+    pub const ALL: [Self; 2] = [Self::Area, Self::Scale];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Area => "Area",
+            Self::Scale => "Scale",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Area => "Make B's area as big as possible",
+            Self::Scale => "Make the longest distance across B as big as possible",
+        }
+    }
+
+    fn value(self, vertices: &[Pos2]) -> f32 {
+        match self {
+            Self::Area => signed_area(vertices),
+            Self::Scale => diameter(vertices),
+        }
+    }
+}
+
+/// What has to stay the same about B's shape as the optimiser changes it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ShapeConstraints {
+    /// Keep every interior angle, so that each edge keeps its direction and only the lengths
+    /// of the edges change.
+    pub fixed_angles: bool,
+    /// Keep the ratio of the width of B's bounding box to its height.
+    pub fixed_aspect: bool,
+    /// Keep the length of this edge, which is the one that ends at the vertex of the same
+    /// index.
+    pub fixed_edge: Option<usize>,
+}
+
+impl ShapeConstraints {
+    /// `vertices` moved as little as possible to keep to the constraints, compared with
+    /// `reference`, or `None` if that can't be done.
+    fn apply(&self, reference: &[Pos2], vertices: &[Pos2]) -> Option<Vec<Pos2>> {
+        let size = f64::from(diameter(reference));
+        let mut vertices = vertices.to_vec();
+        for _ in 0..CONSTRAINT_ROUNDS {
+            if self.fixed_angles {
+                vertices = self.apply_with_fixed_angles(reference, &vertices)?;
+            } else {
+                if let Some(edge) = self.fixed_edge {
+                    fix_edge_length(reference, &mut vertices, edge)?;
+                }
+                if self.fixed_aspect {
+                    fix_aspect(reference, &mut vertices)?;
+                }
+            }
+            if self.error(reference, &vertices) <= CONSTRAINT_TOLERANCE * size {
+                return Some(vertices);
+            }
+        }
+        None
+    }
+
+    /// How far `vertices` are from keeping to the constraints, in length units.
+    fn error(&self, reference: &[Pos2], vertices: &[Pos2]) -> f64 {
+        let mut error = 0.0_f64;
+        if self.fixed_angles {
+            for ((from, to), (reference_from, reference_to)) in
+                edges(vertices).zip(edges(reference))
+            {
+                let direction = (reference_to - reference_from).normalized();
+                let edge = to - from;
+                let across = edge.x * direction.y - edge.y * direction.x;
+                error = error.max(f64::from(across.abs()));
+            }
+        }
+        if let Some(edge) = self.fixed_edge {
+            let length = |vertices: &[Pos2]| edges(vertices).nth(edge).map(|(a, b)| a.distance(b));
+            if let (Some(length), Some(reference_length)) = (length(vertices), length(reference)) {
+                error = error.max(f64::from((length - reference_length).abs()));
+            }
+        }
+        if self.fixed_aspect {
+            let (rect, reference_rect) = (bounding_box(vertices), bounding_box(reference));
+            let wanted_height = rect.x * reference_rect.y / reference_rect.x;
+            error = error.max(f64::from((rect.y - wanted_height).abs()));
+        }
+        error
+    }
+
+    /// With the angles fixed, the shape is set by the lengths of its edges, and every
+    /// constraint is linear in them (as long as the same vertices stay the furthest out in
+    /// each direction), so the nearest lengths that keep to them are found in one go.
+    fn apply_with_fixed_angles(&self, reference: &[Pos2], vertices: &[Pos2]) -> Option<Vec<Pos2>> {
+        let directions = edges(reference)
+            .map(|(from, to)| (to - from).normalized())
+            .collect_vec();
+        let lengths = DVector::from_iterator(
+            directions.len(),
+            edges(vertices)
+                .zip(&directions)
+                .map(|((from, to), direction)| f64::from((to - from).dot(*direction))),
+        );
+
+        // Each row is one linear constraint on the lengths: `row.dot(lengths) = target`
+        let mut rows: Vec<(Vec<f64>, f64)> = vec![
+            (directions.iter().map(|d| f64::from(d.x)).collect(), 0.0),
+            (directions.iter().map(|d| f64::from(d.y)).collect(), 0.0),
+        ];
+        if let Some(edge) = self.fixed_edge {
+            let length = edges(reference).nth(edge).map(|(a, b)| a.distance(b))?;
+            let row = (0..directions.len())
+                .map(|i| if i == edge { 1.0 } else { 0.0 })
+                .collect();
+            rows.push((row, f64::from(length)));
+        }
+        if self.fixed_aspect {
+            rows.push((aspect_row(reference, vertices, &directions), 0.0));
+        }
+
+        let constraints = DMatrix::from_fn(rows.len(), directions.len(), |r, c| {
+            rows.get(r)
+                .and_then(|(row, _)| row.get(c))
+                .copied()
+                .unwrap_or(0.0)
+        });
+        let targets = DVector::from_iterator(rows.len(), rows.iter().map(|&(_, target)| target));
+        let inverse = (&constraints * constraints.transpose())
+            .pseudo_inverse(1e-12)
+            .ok()?;
+        let lengths =
+            &lengths - constraints.transpose() * inverse * (&constraints * &lengths - targets);
+        if lengths.iter().any(|&length| length < 0.0) {
+            return None;
+        }
+
+        // Lay the edges end to end again from the first vertex. Edge 0 ends at vertex 0, so
+        // edge `i` takes vertex `i - 1` to vertex `i`.
+        let start = *vertices.first()?;
+        let rebuilt = std::iter::successors(Some((start, 1)), |&(vertex, i)| {
+            let direction = directions.get(i)?;
+            let length = lengths.get(i)?;
+            Some((vertex + *direction * *length as f32, i + 1))
+        })
+        .map(|(vertex, _)| vertex)
+        .collect_vec();
+        Some(recentred(&rebuilt))
+    }
+}
+
+/// The row that keeps the aspect ratio of the bounding box, with the angles fixed: the
+/// extremes in each direction are vertices, each of which is the first vertex plus the
+/// edges up to it, so the width and height are linear in the edge lengths.
+fn aspect_row(reference: &[Pos2], vertices: &[Pos2], directions: &[Vec2]) -> Vec<f64> {
+    let reference_box = bounding_box(reference);
+    let ratio = f64::from(reference_box.x / reference_box.y);
+
+    let extreme = |key: fn(&Pos2) -> f32, max: bool| {
+        let by_key = vertices.iter().enumerate().map(|(i, v)| (i, key(v)));
+        let found = if max {
+            by_key.max_by(|a, b| a.1.total_cmp(&b.1))
+        } else {
+            by_key.min_by(|a, b| a.1.total_cmp(&b.1))
+        };
+        found.map_or(0, |(i, _)| i)
+    };
+    let (right, left) = (extreme(|v| v.x, true), extreme(|v| v.x, false));
+    let (bottom, top) = (extreme(|v| v.y, true), extreme(|v| v.y, false));
+
+    // How much vertex `j` moves along `key` per unit of length of edge `i`
+    let coefficient = |i: usize, j: usize, component: fn(&Vec2) -> f32| {
+        if (1..=j).contains(&i) {
+            directions.get(i).map_or(0.0, |d| f64::from(component(d)))
+        } else {
+            0.0
+        }
+    };
+    (0..directions.len())
+        .map(|i| {
+            let width = coefficient(i, right, |d| d.x) - coefficient(i, left, |d| d.x);
+            let height = coefficient(i, bottom, |d| d.y) - coefficient(i, top, |d| d.y);
+            width - ratio * height
+        })
+        .collect()
+}
+
+/// Moves both ends of `edge` equally, along the edge, so that it's as long as it is in
+/// `reference`.
+fn fix_edge_length(reference: &[Pos2], vertices: &mut [Pos2], edge: usize) -> Option<()> {
+    let length = edges(reference).nth(edge).map(|(a, b)| a.distance(b))?;
+    let from_index = edge
+        .checked_sub(1)
+        .unwrap_or(vertices.len().checked_sub(1)?);
+    let (from, to) = (*vertices.get(from_index)?, *vertices.get(edge)?);
+    let middle = from.lerp(to, 0.5);
+    let half = (to - from).normalized() * length / 2.0;
+    *vertices.get_mut(from_index)? = middle - half;
+    *vertices.get_mut(edge)? = middle + half;
+    Some(())
+}
+
+/// Stretches `vertices` up or down about their centroid so that their bounding box has the
+/// same aspect ratio as that of `reference`.
+fn fix_aspect(reference: &[Pos2], vertices: &mut [Pos2]) -> Option<()> {
+    let (rect, reference_rect) = (bounding_box(vertices), bounding_box(reference));
+    let factor = rect.x * reference_rect.y / (reference_rect.x * rect.y);
+    if !factor.is_finite() || factor <= 0.0 {
+        return None;
+    }
+    let centroid = vertex_centroid(vertices);
+    for vertex in vertices {
+        vertex.y = centroid.y + (vertex.y - centroid.y) * factor;
+    }
+    Some(())
+}
+
+/// The width and height of the box around `vertices`.
+fn bounding_box(vertices: &[Pos2]) -> Vec2 {
+    egui::Rect::from_points(vertices).size()
+}
+
+fn recentred(vertices: &[Pos2]) -> Vec<Pos2> {
+    let centroid = vertex_centroid(vertices).to_vec2();
+    vertices.iter().map(|&v| v - centroid).collect()
+}
+
+/// Grows B, a little at a time, for as long as it still fits inside A.
+///
+/// This is a pattern search: each move either grows all of B at once or nudges one of its
+/// vertices one of four ways, after which the shape is put back within the constraints.
+/// A move is kept if it makes the objective bigger and B still fits. Once none of the moves
+/// work, they're made smaller, until they're too small to matter.
+pub struct ShapeOptimiser {
+    objective: ShapeObjective,
+    constraints: ShapeConstraints,
+    /// B as it was to begin with, centred on its centroid, which the constraints compare
+    /// against.
+    reference: Vec<Pos2>,
+    vertices: Vec<Pos2>,
+    value: f32,
+    /// How far moves go, relative to the size of B.
+    step: f32,
+    /// Which move to try next. Move 0 grows B, and move `1 + 4 i + k` nudges vertex `i`.
+    next_move: usize,
+    /// How many moves have been tried in a row without any of them working.
+    failures: usize,
+    accepted: usize,
+}
+
+impl ShapeOptimiser {
+    /// Starts from `vertices`, which should fit, and be centred on their centroid.
+    pub fn new(
+        vertices: &[Pos2],
+        objective: ShapeObjective,
+        constraints: ShapeConstraints,
+    ) -> Self {
+        Self {
+            objective,
+            constraints,
+            reference: vertices.to_vec(),
+            vertices: vertices.to_vec(),
+            value: objective.value(vertices),
+            step: START_STEP,
+            next_move: 0,
+            failures: 0,
+            accepted: 0,
+        }
+    }
+
+    pub fn objective(&self) -> ShapeObjective {
+        self.objective
+    }
+
+    /// The best shape found so far, centred on its centroid.
+    pub fn vertices(&self) -> &[Pos2] {
+        &self.vertices
+    }
+
+    /// The objective for the best shape found so far.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// How many moves have made B bigger.
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step < MIN_STEP
+    }
+
+    fn num_moves(&self) -> usize {
+        1 + 4 * self.vertices.len()
+    }
+
+    /// Tries the next move, keeping it if it helps.
+    pub fn try_next_move(
+        &mut self,
+        solver: &dyn FitSolver,
+        outer_vertices: &[Pos2],
+        settings: &SolverSettings,
+    ) {
+        if self.is_finished() {
+            return;
+        }
+
+        let candidate = self.moved(self.next_move);
+        let better = candidate.filter(|candidate| {
+            is_convex(candidate)
+                && self.objective.value(candidate) > self.value
+                && matches!(
+                    solve_orientations(solver, outer_vertices, candidate, settings).fit,
+                    Fit::Fit { .. }
+                )
+        });
+
+        if let Some(candidate) = better {
+            // Keep making the same move while it works
+            self.value = self.objective.value(&candidate);
+            self.vertices = candidate;
+            self.failures = 0;
+            self.accepted += 1;
+        } else {
+            self.failures += 1;
+            self.next_move = (self.next_move + 1) % self.num_moves();
+            if self.failures >= self.num_moves() {
+                self.step /= 2.0;
+                self.failures = 0;
+            }
+        }
+    }
+
+    /// B after move `index`, put back within the constraints.
+    fn moved(&self, index: usize) -> Option<Vec<Pos2>> {
+        let distance = self.step * diameter(&self.vertices);
+        let mut vertices = self.vertices.clone();
+        match index.checked_sub(1) {
+            None => {
+                for vertex in &mut vertices {
+                    *vertex = (vertex.to_vec2() * (1.0 + self.step)).to_pos2();
+                }
+            }
+            Some(nudge) => {
+                let directions = [Vec2::X, Vec2::Y, -Vec2::X, -Vec2::Y];
+                let direction = directions.get(nudge % 4).copied()?;
+                *vertices.get_mut(nudge / 4)? += direction * distance;
+            }
+        }
+        let vertices = self.constraints.apply(&self.reference, &vertices)?;
+        Some(recentred(&vertices))
+    }
+}
@@ -0,0 +1,261 @@
+use std::f64::consts::TAU;
+
+use egui::Pos2;
+use itertools::Itertools as _;
+use nalgebra::Vector2;
+
+use super::{
+    NUM_POINTS, SolverSettings, inset_half_planes, max_min_slack_within, room_constraints,
+    tightest_vertices, to_vectors,
+};
+
+/// How many times to narrow in on the best angle found by the sweep.
+const REFINEMENT_STEPS: usize = 40;
+
+/// Where B pokes out of A the least, when it doesn't fit.
+#[derive(Clone, Copy, Debug)]
+pub struct Overflow {
+    pub angle: f64,
+    pub translation: Vector2<f64>,
+    /// How far B's furthest vertex lies past any of A's edges (moved inwards by any
+    /// clearance) at this pose. This is how far A's edges would all have to move outwards for
+    /// B to fit.
+    pub depth: f64,
+    /// What B would have to be scaled by, about its centroid, to fit. Not positive if it
+    /// can't fit at any size, which happens when the clearance is too big for A.
+    pub scale: Option<f64>,
+}
+
+/// Finds the pose at which B overflows A the least, measured by how far its furthest vertex
+/// pokes out past any of A's edges, and how much smaller B would have to be to fit.
+///
+/// This keeps to the restrictions and the clearance in `settings`, and tries the same angles
+/// as the other sweeps. Both sets of vertices are expected to be centred on their centroids,
+/// and defined anti-clockwise. Returns `None` if there's no allowed pose to try.
+pub fn min_overflow(
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    settings: &SolverSettings,
+) -> Option<Overflow> {
+    let planes = inset_half_planes(&to_vectors(outer_vertices), settings.clearance);
+    let inner = to_vectors(inner_vertices);
+    let restrictions = &settings.restrictions;
+    let (bounds, origin) = restrictions.translation.as_ref().map_or_else(
+        || (Vec::new(), Vector2::zeros()),
+        |bound| (bound.half_planes(), bound.interior_point()),
+    );
+    let angles = restrictions
+        .angles
+        .sweep(NUM_POINTS)
+        .into_iter()
+        .filter(|&angle| restrictions.angles.allows(angle))
+        .collect_vec();
+
+    // The roomiest translation is the one that pokes out the least, when there's no room
+    let roomiest = |angle: f64| {
+        let constraints = room_constraints(&planes, &inner, angle);
+        max_min_slack_within(&constraints, &bounds, origin)
+    };
+    let angle = best_angle(&angles, |angle| roomiest(angle).map(|(_, slack)| slack))?;
+    let (translation, slack) = roomiest(angle)?;
+
+    // Scaling B by `k` scales how far it reaches towards each edge, `c - g`, so it fits when
+    // `n.dot(t) + k (c - g) <= c` for every edge. Dividing each of those through by `c - g`
+    // makes this another max-min slack problem, with `k` as the slack.
+    let largest_scale = |angle: f64| {
+        let constraints = planes
+            .iter()
+            .zip(tightest_vertices(&planes, &inner, angle))
+            .map(|(&(n, c), (_, g))| {
+                let reach = c - g;
+                (reach > 0.0).then(|| (n / reach, c / reach))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        max_min_slack_within(&constraints, &bounds, origin).map(|(_, scale)| scale)
+    };
+    let scale = best_angle(&angles, largest_scale).and_then(largest_scale);
+
+    Some(Overflow {
+        angle,
+        translation,
+        depth: -slack,
+        scale,
+    })
+}
+
+/// The angle at which `f` is largest: the best of `angles`, narrowed down by ternary search
+/// between its neighbours as long as both of them are in `angles` too.
+fn best_angle(angles: &[f64], f: impl Fn(f64) -> Option<f64>) -> Option<f64> {
+    let values = angles.iter().map(|&angle| f(angle)).collect_vec();
+    let (best, _) = values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| value.map(|value| (i, value)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let angle = angles.get(best).copied()?;
+
+    // Only refine where the angles are evenly spread, so as not to stray out of an allowed
+    // range
+    let step = TAU / NUM_POINTS as f64;
+    let (Some(&before), Some(&after)) = (
+        best.checked_sub(1).and_then(|i| angles.get(i)),
+        angles.get(best + 1),
+    ) else {
+        return Some(angle);
+    };
+    if (angle - before - step).abs() > 1e-9 || (after - angle - step).abs() > 1e-9 {
+        return Some(angle);
+    }
+
+    let value = |angle: f64| f(angle).unwrap_or(f64::NEG_INFINITY);
+    let (mut lo, mut hi) = (before, after);
+    for _ in 0..REFINEMENT_STEPS {
+        let m_1 = lo + (hi - lo) / 3.0;
+        let m_2 = hi - (hi - lo) / 3.0;
+        if value(m_1) < value(m_2) {
+            lo = m_1;
+        } else {
+            hi = m_2;
+        }
+    }
+    let refined = f64::midpoint(lo, hi);
+    Some(if value(refined) > value(angle) {
+        refined
+    } else {
+        angle
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::fit::{
+        SolverKind, calculate_data, solve,
+        test_support::{centred, even_sweep},
+    };
+
+    /// A square with sides `2 * half_width`, centred on the origin.
+    fn square(half_width: f32) -> Vec<Pos2> {
+        centred(&[
+            (-half_width, -half_width),
+            (half_width, -half_width),
+            (half_width, half_width),
+            (-half_width, half_width),
+        ])
+    }
+
+    /// How far `angle` is from the nearest multiple of a quarter turn.
+    fn off_square(angle: f64) -> f64 {
+        let offset = angle.rem_euclid(FRAC_PI_2);
+        offset.min(FRAC_PI_2 - offset)
+    }
+
+    #[test]
+    fn measures_how_far_a_square_pokes_out() {
+        let overflow = min_overflow(&square(1.0), &square(1.5), &even_sweep())
+            .expect("every angle is allowed");
+        assert!((overflow.depth - 0.5).abs() < 1e-6, "{overflow:?}");
+        assert!(off_square(overflow.angle) < 1e-6, "{overflow:?}");
+        assert!(overflow.translation.norm() < 1e-6, "{overflow:?}");
+        let scale = overflow.scale.expect("B fits if it's small enough");
+        assert!((scale - 2.0 / 3.0).abs() < 1e-6, "{scale}");
+    }
+
+    #[test]
+    fn gives_the_room_to_spare_when_it_fits() {
+        let overflow = min_overflow(&square(1.0), &square(0.5), &even_sweep())
+            .expect("every angle is allowed");
+        assert!((overflow.depth + 0.5).abs() < 1e-6, "{overflow:?}");
+        let scale = overflow.scale.expect("B fits");
+        assert!((scale - 2.0).abs() < 1e-6, "{scale}");
+    }
+
+    #[test]
+    fn keeps_clear_of_the_edges() {
+        let settings = SolverSettings {
+            clearance: 0.25,
+            ..even_sweep()
+        };
+        let overflow =
+            min_overflow(&square(1.0), &square(0.5), &settings).expect("every angle is allowed");
+        assert!((overflow.depth + 0.25).abs() < 1e-6, "{overflow:?}");
+        let scale = overflow.scale.expect("B fits");
+        assert!((scale - 1.5).abs() < 1e-6, "{scale}");
+
+        // The clearance leaves no room at all, so B can't fit whatever its size
+        let settings = SolverSettings {
+            clearance: 1.0,
+            ..even_sweep()
+        };
+        let overflow =
+            min_overflow(&square(1.0), &square(0.5), &settings).expect("every angle is allowed");
+        assert!((overflow.depth - 0.5).abs() < 1e-6, "{overflow:?}");
+        assert!(
+            overflow.scale.is_none_or(|scale| scale <= 0.0),
+            "{overflow:?}"
+        );
+    }
+
+    #[test]
+    fn scaling_by_the_answer_just_fits() {
+        let outer = centred(&[(-60.0, -40.0), (70.0, -30.0), (-10.0, 60.0)]);
+        let inner = centred(&[(-45.0, -30.0), (60.0, -37.5), (52.5, 45.0), (-30.0, 37.5)]);
+        let settings = even_sweep();
+        let overflow = min_overflow(&outer, &inner, &settings).expect("every angle is allowed");
+        assert!(overflow.depth > 0.0, "{overflow:?}");
+        let scale = overflow.scale.expect("B fits if it's small enough") as f32;
+
+        let resized = |k: f32| {
+            inner
+                .iter()
+                .map(|&v| (k * v.to_vec2()).to_pos2())
+                .collect_vec()
+        };
+        let margin = |k: f32| calculate_data(&outer, &resized(k), &settings).margin;
+        assert!(margin(0.99 * scale).is_some_and(|margin| margin > 0.0));
+        assert!(margin(1.01 * scale).is_none_or(|margin| margin < 0.0));
+    }
+
+    #[test]
+    fn max_clearance_does_not_depend_on_the_clearance() {
+        let triangle = centred(&[(-60.0, -40.0), (70.0, -30.0), (-10.0, 60.0)]);
+        let cases = [
+            (
+                SolverKind::LinearProgram,
+                square(1.0),
+                square(0.5),
+                Some(0.5),
+            ),
+            (SolverKind::CriticalRegion, triangle, square(5.0), None),
+        ];
+        for (kind, outer, inner, expected) in cases {
+            let max_clearance = |clearance: f64| {
+                let settings = SolverSettings {
+                    clearance,
+                    ..even_sweep()
+                };
+                let data = solve(kind.solver(), &outer, &inner, &settings);
+                let margin = data.margin.expect("B fits");
+                let max_clearance = data.max_clearance.expect("B fits");
+                assert!(
+                    (max_clearance - margin - clearance).abs() < 1e-9,
+                    "{clearance}: {margin} + {clearance} != {max_clearance}"
+                );
+                max_clearance
+            };
+            let without = max_clearance(0.0);
+            if let Some(expected) = expected {
+                assert!((without - expected).abs() < 1e-6, "{without}");
+            }
+            for clearance in [0.1, 0.2, 0.4] {
+                let with = max_clearance(clearance * without);
+                assert!(
+                    (with - without).abs() < 1e-6 * without,
+                    "{with} != {without}"
+                );
+            }
+        }
+    }
+}
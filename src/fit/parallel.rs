@@ -0,0 +1,28 @@
+/// `f` of each of `items`, in order, shared out between as many threads as there are to
+/// spare.
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn in_parallel<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let num_threads = std::thread::available_parallelism().map_or(1, std::num::NonZero::get);
+    let chunk_size = items.len().div_ceil(num_threads).max(1);
+    std::thread::scope(|scope| {
+        let f = &f;
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// There are no threads on the web, so this just works through `items` in turn.
+#[cfg(target_arch = "wasm32")]
+pub(super) fn in_parallel<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    items.iter().map(f).collect()
+}
@@ -0,0 +1,365 @@
+use egui::{Pos2, Rect, Vec2};
+use itertools::Itertools as _;
+
+use super::{Fit, SolverKind, SolverSettings, Which, solver::solve};
+use crate::geometry::{is_convex, vertex_centroid};
+
+/// A measure of one of the polygons' shapes that a [`ParameterSweep`] varies.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum SweepParameter {
+    /// How far one vertex is moved from where it is now, along a line at `angle` radians.
+    VertexOffset {
+        polygon: Which,
+        vertex: usize,
+        angle: f64,
+    },
+    /// The ratio of the width of the polygon's bounding box to its height, got by
+    /// stretching it up or down.
+    AspectRatio(Which),
+    /// How much the polygon is scaled up by, about its centroid.
+    Scale(Which),
+}
+
+impl Default for SweepParameter {
+    fn default() -> Self {
+        Self::Scale(Which::Inner)
+    }
+}
+
+impl SweepParameter {
+    pub fn polygon(self) -> Which {
+        match self {
+            Self::VertexOffset { polygon, .. }
+            | Self::AspectRatio(polygon)
+            | Self::Scale(polygon) => polygon,
+        }
+    }
+
+    /// A short name for the parameter, such as "B3 offset".
+    pub fn name(self) -> String {
+        match self {
+            Self::VertexOffset {
+                polygon, vertex, ..
+            } => format!("{}{} offset", polygon.name(), vertex + 1),
+            Self::AspectRatio(polygon) => format!("{} aspect ratio", polygon.name()),
+            Self::Scale(polygon) => format!("{} scale", polygon.name()),
+        }
+    }
+
+    /// The parameter's value for the polygons as they are.
+    pub fn current_value(self, outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> f64 {
+        match self {
+            Self::VertexOffset { .. } => 0.0,
+            Self::AspectRatio(polygon) => {
+                let size = Rect::from_points(pick(polygon, outer_vertices, inner_vertices)).size();
+                f64::from(size.x / size.y)
+            }
+            Self::Scale(_) => 1.0,
+        }
+    }
+
+    /// A range of values to sweep over that's in proportion to the polygons as they are.
+    pub fn default_range(self, outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> (f64, f64) {
+        match self {
+            Self::VertexOffset { polygon, .. } => {
+                let size = Rect::from_points(pick(polygon, outer_vertices, inner_vertices)).size();
+                let reach = f64::from(size.max_elem()) / 4.0;
+                (-reach, reach)
+            }
+            Self::AspectRatio(_) | Self::Scale(_) => {
+                let value = self.current_value(outer_vertices, inner_vertices);
+                (value / 2.0, value * 2.0)
+            }
+        }
+    }
+
+    /// Both polygons, centred on their centroids again, with the parameter set to `value`.
+    /// `None` if that doesn't leave a convex polygon.
+    pub fn apply(
+        self,
+        value: f64,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+    ) -> Option<(Vec<Pos2>, Vec<Pos2>)> {
+        let mut outer = outer_vertices.to_vec();
+        let mut inner = inner_vertices.to_vec();
+        let vertices = match self.polygon() {
+            Which::Outer => &mut outer,
+            Which::Inner => &mut inner,
+        };
+        let value = value as f32;
+        let centroid = vertex_centroid(vertices);
+
+        match self {
+            Self::VertexOffset { vertex, angle, .. } => {
+                *vertices.get_mut(vertex)? += value * Vec2::angled(angle as f32);
+            }
+            Self::AspectRatio(_) => {
+                let size = Rect::from_points(vertices).size();
+                let factor = size.x / (value * size.y);
+                if !factor.is_finite() || factor <= 0.0 {
+                    return None;
+                }
+                for vertex in vertices.iter_mut() {
+                    vertex.y = centroid.y + (vertex.y - centroid.y) * factor;
+                }
+            }
+            Self::Scale(_) => {
+                if value <= 0.0 {
+                    return None;
+                }
+                for vertex in vertices.iter_mut() {
+                    *vertex = centroid + (*vertex - centroid) * value;
+                }
+            }
+        }
+
+        let recentre = |vertices: Vec<Pos2>| {
+            let centroid = vertex_centroid(&vertices).to_vec2();
+            vertices
+                .into_iter()
+                .map(|v| v - centroid)
+                .collect::<Vec<_>>()
+        };
+        let (outer, inner) = (recentre(outer), recentre(inner));
+        (is_convex(&outer) && is_convex(&inner)).then_some((outer, inner))
+    }
+}
+
+fn pick<'a>(polygon: Which, outer_vertices: &'a [Pos2], inner_vertices: &'a [Pos2]) -> &'a [Pos2] {
+    match polygon {
+        Which::Outer => outer_vertices,
+        Which::Inner => inner_vertices,
+    }
+}
+
+/// What came of solving for one value of a [`SweepParameter`], or one cell of a
+/// [`FeasibilityMap`](super::FeasibilityMap).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SweepOutcome {
+    Fit,
+    NoFit,
+    /// The parameter left one of the polygons not convex, so there was nothing to solve.
+    NotConvex,
+    /// The solver can't handle A as the parameter left it.
+    Unsupported,
+}
+
+impl SweepOutcome {
+    pub const ALL: [Self; 4] = [Self::Fit, Self::NoFit, Self::NotConvex, Self::Unsupported];
+
+    /// What the solver made of the polygons, which were convex.
+    pub fn of<T>(fit: &Fit<T>) -> Self {
+        match fit {
+            Fit::Fit { .. } => Self::Fit,
+            Fit::NoFit => Self::NoFit,
+            Fit::Unsupported => Self::Unsupported,
+        }
+    }
+
+    pub fn fits(self) -> bool {
+        self == Self::Fit
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fit => "Fits",
+            Self::NoFit => "No fit",
+            Self::NotConvex => "Not convex",
+            Self::Unsupported => "Unsupported",
+        }
+    }
+}
+
+/// How B fared at one value of the parameter.
+#[derive(Clone, Copy, Debug)]
+pub struct SweepPoint {
+    pub value: f64,
+    /// `None` if there was nothing solved at this value, or there was no margin to give.
+    pub margin: Option<f64>,
+    /// The angle that B fits at, or that it overflows A the least at if it doesn't.
+    pub angle: Option<f64>,
+    pub outcome: SweepOutcome,
+}
+
+/// Runs the solver for evenly spaced values of a [`SweepParameter`], one at a time so that
+/// it can be spread over several frames.
+pub struct ParameterSweep {
+    parameter: SweepParameter,
+    range: (f64, f64),
+    num_steps: usize,
+    outer_vertices: Vec<Pos2>,
+    inner_vertices: Vec<Pos2>,
+    solver: SolverKind,
+    settings: SolverSettings,
+    points: Vec<SweepPoint>,
+}
+
+impl ParameterSweep {
+    /// A sweep from `range.0` to `range.1` in `num_steps` steps (so `num_steps + 1` values),
+    /// starting from the polygons as they are, which should be centred on their centroids.
+    ///
+    /// The pre-checks are skipped, as those that rule B out don't give a margin, and so is
+    /// the proof.
+    pub fn new(
+        parameter: SweepParameter,
+        range: (f64, f64),
+        num_steps: usize,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Self {
+        Self {
+            parameter,
+            range,
+            num_steps: num_steps.max(1),
+            outer_vertices: outer_vertices.to_vec(),
+            inner_vertices: inner_vertices.to_vec(),
+            solver,
+            settings: SolverSettings {
+                pre_checks: false,
+                verify: false,
+                ..settings.clone()
+            },
+            points: Vec::new(),
+        }
+    }
+
+    pub fn parameter(&self) -> SweepParameter {
+        self.parameter
+    }
+
+    /// The results so far, in order of the parameter.
+    pub fn points(&self) -> &[SweepPoint] {
+        &self.points
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.points.len() > self.num_steps
+    }
+
+    /// How far through the sweep is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.points.len() as f32 / (self.num_steps + 1) as f32
+    }
+
+    /// Solves for the next value of the parameter.
+    pub fn run_next(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+
+        let (start, end) = self.range;
+        let t = self.points.len() as f64 / self.num_steps as f64;
+        let value = start + (end - start) * t;
+        let point = match self
+            .parameter
+            .apply(value, &self.outer_vertices, &self.inner_vertices)
+        {
+            Some((outer, inner)) => {
+                let data = solve(self.solver.solver(), &outer, &inner, &self.settings);
+                let angle = match data.fit {
+                    Fit::Fit { angle, .. } => Some(angle),
+                    Fit::NoFit | Fit::Unsupported => data.overflow.map(|overflow| overflow.angle),
+                };
+                SweepPoint {
+                    value,
+                    margin: data.margin,
+                    angle,
+                    outcome: SweepOutcome::of(&data.fit),
+                }
+            }
+            None => SweepPoint {
+                value,
+                margin: None,
+                angle: None,
+                outcome: SweepOutcome::NotConvex,
+            },
+        };
+        self.points.push(point);
+    }
+
+    /// The results as comma-separated values, with a header row.
+    pub fn to_csv(&self) -> String {
+        let header = format!("{},margin,angle_degrees,outcome", self.parameter.name());
+        let optional = |x: Option<f64>| x.map(|x| x.to_string()).unwrap_or_default();
+        let lines = self.points.iter().map(|point| {
+            format!(
+                "{},{},{},{}",
+                point.value,
+                optional(point.margin),
+                optional(point.angle.map(f64::to_degrees)),
+                point.outcome.name()
+            )
+        });
+        std::iter::once(header).chain(lines).join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fit::test_support::{centred, even_sweep};
+
+    fn run(mut sweep: ParameterSweep) -> Vec<SweepOutcome> {
+        while !sweep.is_finished() {
+            sweep.run_next();
+        }
+        sweep.points().iter().map(|point| point.outcome).collect()
+    }
+
+    #[test]
+    fn tells_what_couldnt_be_solved_apart_from_no_fit() {
+        let square = centred(&[(-50.0, -50.0), (50.0, -50.0), (50.0, 50.0), (-50.0, 50.0)]);
+        let triangle = centred(&[(-10.0, -10.0), (10.0, -10.0), (0.0, 10.0)]);
+
+        // Pulling B's top vertex down through its base turns it inside out
+        let offset = SweepParameter::VertexOffset {
+            polygon: Which::Inner,
+            vertex: 2,
+            angle: -std::f64::consts::FRAC_PI_2,
+        };
+        let sweep = ParameterSweep::new(
+            offset,
+            (0.0, 40.0),
+            2,
+            &square,
+            &triangle,
+            SolverKind::LinearProgram,
+            &even_sweep(),
+        );
+        assert_eq!(
+            run(sweep),
+            [
+                SweepOutcome::Fit,
+                SweepOutcome::Fit,
+                SweepOutcome::NotConvex
+            ]
+        );
+
+        // The critical region can't handle a square A at any scale
+        let scale = SweepParameter::Scale(Which::Inner);
+        let sweep = ParameterSweep::new(
+            scale,
+            (1.0, 20.0),
+            1,
+            &square,
+            &triangle,
+            SolverKind::CriticalRegion,
+            &even_sweep(),
+        );
+        assert_eq!(run(sweep), [SweepOutcome::Unsupported; 2]);
+
+        let sweep = ParameterSweep::new(
+            scale,
+            (1.0, 20.0),
+            1,
+            &square,
+            &triangle,
+            SolverKind::LinearProgram,
+            &even_sweep(),
+        );
+        assert_eq!(run(sweep), [SweepOutcome::Fit, SweepOutcome::NoFit]);
+    }
+}
@@ -0,0 +1,80 @@
+use egui::{Pos2, Vec2};
+
+use crate::geometry::{diameter, enclosing_circle, inscribed_circle, min_width, signed_area};
+
+/// A cheap test that can settle whether the inner polygon fits, without sweeping through
+/// every angle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreCheck {
+    /// B has more area than A, so can't fit.
+    AreaTooLarge,
+    /// B has two vertices further apart than any two points of A, so can't fit.
+    DiameterTooLarge,
+    /// B is wider than A in every direction A is narrowest in, so can't fit.
+    MinWidthTooLarge,
+    /// The smallest circle around B fits inside the largest circle inside A, so B fits at
+    /// every angle.
+    CircumcircleInIncircle,
+}
+
+impl PreCheck {
+    /// Whether passing this check means there is a fit (rather than there being no fit).
+    pub fn fits(self) -> bool {
+        match self {
+            Self::AreaTooLarge | Self::DiameterTooLarge | Self::MinWidthTooLarge => false,
+            Self::CircumcircleInIncircle => true,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::AreaTooLarge => "B has a larger area than A",
+            Self::DiameterTooLarge => "B has a larger diameter than A",
+            Self::MinWidthTooLarge => "B has a larger minimum width than A",
+            Self::CircumcircleInIncircle => "B's circumcircle fits inside A's incircle",
+        }
+    }
+}
+
+/// The outcome of a conclusive pre-check: which check it was, and (if it found a fit) a
+/// translation that places B inside A at zero rotation.
+pub struct PreCheckResult {
+    pub check: PreCheck,
+    pub translation: Option<Vec2>,
+}
+
+/// Runs the cheapest tests first, stopping at the first one that's conclusive. Each
+/// measurement is only made once the tests before it have failed to settle the answer.
+///
+/// Both sets of vertices are expected to be centred on their centroids, and defined
+/// anti-clockwise.
+pub fn pre_check(outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> Option<PreCheckResult> {
+    let no_fit = |check| {
+        Some(PreCheckResult {
+            check,
+            translation: None,
+        })
+    };
+    if signed_area(inner_vertices) > signed_area(outer_vertices) {
+        return no_fit(PreCheck::AreaTooLarge);
+    }
+    if diameter(inner_vertices) > diameter(outer_vertices) {
+        return no_fit(PreCheck::DiameterTooLarge);
+    }
+    let ((inner_width, _), (outer_width, _)) =
+        (min_width(inner_vertices), min_width(outer_vertices));
+    if inner_width > outer_width {
+        return no_fit(PreCheck::MinWidthTooLarge);
+    }
+
+    let (circumradius, circumcentre) = enclosing_circle(inner_vertices);
+    let (inradius, incentre) = inscribed_circle(outer_vertices);
+    if circumradius < inradius {
+        return Some(PreCheckResult {
+            check: PreCheck::CircumcircleInIncircle,
+            translation: Some(incentre - circumcentre),
+        });
+    }
+
+    None
+}
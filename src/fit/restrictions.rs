@@ -0,0 +1,288 @@
+use std::f64::consts::TAU;
+
+use egui::{Pos2, Rect};
+use nalgebra::Vector2;
+
+use super::{FeasibleInterval, Real, half_planes, to_vectors};
+
+/// How close (in radians) an angle has to be to an allowed one to count as allowed, so that
+/// round-off doesn't rule out the ends of an interval or a fixed angle.
+const ANGLE_SLOP: f64 = 1e-6;
+
+/// Limits on how B may be placed inside A, on top of having to fit.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Restrictions {
+    pub angles: AngleRestriction,
+    /// Where B's centroid may be moved to, measured from A's centroid, if anywhere isn't
+    /// allowed.
+    pub translation: Option<TranslationBound>,
+}
+
+impl Restrictions {
+    /// Whether there are any restrictions at all. If there are, the polygons' symmetries, the
+    /// pre-checks, the exact check and the proof no longer apply, as none of them know about
+    /// the restrictions.
+    pub fn is_active(&self) -> bool {
+        self.angles != AngleRestriction::Free || self.translation.is_some()
+    }
+}
+
+/// Which angles B may be turned to.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum AngleRestriction {
+    #[default]
+    Free,
+    /// Only the angles within one of these intervals.
+    Intervals(Vec<AngleInterval>),
+    /// Only this one angle, in radians: B may slide about, but not turn.
+    Fixed(f64),
+}
+
+/// A range of angles in radians, from `start` anti-clockwise round to `end`. Either end may
+/// be negative or past a whole turn.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AngleInterval {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl AngleInterval {
+    /// The interval moved round by whole turns so that it starts in `[0, 2π)`, and cut down
+    /// to a whole turn if it's longer.
+    fn normalised(self) -> Self {
+        let start = self.start.rem_euclid(TAU);
+        Self {
+            start,
+            end: start + (self.end - self.start).clamp(0.0, TAU),
+        }
+    }
+
+    /// Where the normalised interval lies within `[0, 2π]`, as up to two pieces.
+    fn pieces(self) -> impl Iterator<Item = (f64, f64)> {
+        let Self { start, end } = self.normalised();
+        [(start, end.min(TAU)), (0.0, end - TAU)]
+            .into_iter()
+            .filter(|(start, end)| end >= start)
+    }
+}
+
+impl AngleRestriction {
+    /// The allowed angles, as intervals starting within `[0, 2π)`.
+    pub fn allowed_intervals(&self) -> Vec<AngleInterval> {
+        match self {
+            Self::Free => vec![AngleInterval {
+                start: 0.0,
+                end: TAU,
+            }],
+            Self::Intervals(intervals) => intervals.iter().map(|i| i.normalised()).collect(),
+            &Self::Fixed(angle) => vec![
+                AngleInterval {
+                    start: angle,
+                    end: angle,
+                }
+                .normalised(),
+            ],
+        }
+    }
+
+    pub fn allows(&self, angle: f64) -> bool {
+        match self {
+            Self::Free => true,
+            Self::Intervals(intervals) => intervals.iter().any(|interval| {
+                let interval = interval.normalised();
+                let offset = (angle - interval.start + ANGLE_SLOP).rem_euclid(TAU);
+                offset <= interval.end - interval.start + 2.0 * ANGLE_SLOP
+            }),
+            &Self::Fixed(fixed) => {
+                let offset = (angle - fixed).rem_euclid(TAU);
+                offset <= ANGLE_SLOP || offset >= TAU - ANGLE_SLOP
+            }
+        }
+    }
+
+    /// The angles within `[0, 2π)` where the allowed angles start or stop, which a sweep
+    /// has to look at, as the best pose is often right up against one of them.
+    pub fn boundaries(&self) -> Vec<f64> {
+        match self {
+            Self::Free => Vec::new(),
+            Self::Intervals(intervals) => intervals
+                .iter()
+                .flat_map(|interval| {
+                    let interval = interval.normalised();
+                    [interval.start, interval.end.rem_euclid(TAU)]
+                })
+                .collect(),
+            Self::Fixed(angle) => vec![angle.rem_euclid(TAU)],
+        }
+    }
+
+    /// `num_angles` evenly spread over a whole turn, along with the ends of the allowed
+    /// angles, in order.
+    pub fn sweep(&self, num_angles: usize) -> Vec<f64> {
+        let mut xs: Vec<f64> = (0..num_angles)
+            .map(|i| TAU * i as f64 / num_angles as f64)
+            .chain(self.boundaries())
+            .collect();
+        xs.sort_by(f64::total_cmp);
+        xs.dedup();
+        xs
+    }
+
+    /// The ranges of angles within `[0, 2π]` that aren't allowed, in order.
+    pub fn masked_intervals(&self) -> Vec<(f64, f64)> {
+        if *self == Self::Free {
+            return Vec::new();
+        }
+
+        let mut pieces: Vec<(f64, f64)> = self
+            .allowed_intervals()
+            .into_iter()
+            .flat_map(AngleInterval::pieces)
+            .collect();
+        pieces.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut masked = Vec::new();
+        let mut covered_to = 0.0;
+        for (start, end) in pieces {
+            if start > covered_to {
+                masked.push((covered_to, start));
+            }
+            covered_to = end.max(covered_to);
+        }
+        if covered_to < TAU {
+            masked.push((covered_to, TAU));
+        }
+        masked
+    }
+
+    /// Cuts the feasible `intervals` down to the allowed angles, finding the peak within each
+    /// piece again from the samples of the minimum curve.
+    ///
+    /// The ends of the allowed angles should be among the samples, so that every piece has
+    /// at least one in it. Pieces where none of the samples are above `threshold` are
+    /// dropped.
+    pub fn clip<T: Real>(
+        &self,
+        intervals: Vec<FeasibleInterval<T>>,
+        xs: &[T],
+        min_ys: &[T],
+        threshold: T,
+    ) -> Vec<FeasibleInterval<T>> {
+        if *self == Self::Free {
+            return intervals;
+        }
+
+        let allowed = self.allowed_intervals();
+        let mut clipped: Vec<FeasibleInterval<T>> = intervals
+            .iter()
+            .flat_map(|interval| {
+                let (start, end) = (interval.start.to_double(), interval.end.to_double());
+                allowed.iter().flat_map(move |allowed| {
+                    [-TAU, 0.0, TAU].into_iter().filter_map(move |shift| {
+                        let lo = start.max(allowed.start + shift);
+                        let hi = end.min(allowed.end + shift);
+                        // Keep to the convention of starting within the first turn
+                        let turns = (lo / TAU).floor() * TAU;
+                        (lo <= hi).then_some((lo - turns, hi - turns))
+                    })
+                })
+            })
+            .filter_map(|(lo, hi)| {
+                let (peak_angle, peak_margin) = xs
+                    .iter()
+                    .zip(min_ys)
+                    .filter_map(|(&x, &y)| {
+                        let x = x.to_double();
+                        [x, x + TAU]
+                            .into_iter()
+                            .find(|x| (lo - ANGLE_SLOP..=hi + ANGLE_SLOP).contains(x))
+                            .map(|x| (x, y))
+                    })
+                    .reduce(|best, pair| if pair.1 > best.1 { pair } else { best })?;
+                (peak_margin > threshold).then(|| FeasibleInterval {
+                    start: T::from_double(lo),
+                    end: T::from_double(hi),
+                    peak_angle: T::from_double(peak_angle),
+                    peak_margin,
+                })
+            })
+            .collect();
+        clipped.sort_by(|a, b| {
+            a.start
+                .partial_cmp(&b.start)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        clipped
+    }
+}
+
+/// A region that B's centroid has to stay within, measured from A's centroid, such as the
+/// slot that a part slides along.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum TranslationBound {
+    Box(Rect),
+    /// A convex polygon, defined anti-clockwise.
+    Polygon(Vec<Pos2>),
+}
+
+impl TranslationBound {
+    /// The region as half-planes `(n, c)`, so that the translation `t` is allowed when
+    /// `n.dot(t) <= c` for each of them.
+    pub fn half_planes<T: Real>(&self) -> Vec<(Vector2<T>, T)> {
+        match self {
+            Self::Box(rect) => {
+                let one = T::one();
+                let zero = T::zero();
+                vec![
+                    (Vector2::new(one, zero), T::from_single(rect.max.x)),
+                    (Vector2::new(-one, zero), -T::from_single(rect.min.x)),
+                    (Vector2::new(zero, one), T::from_single(rect.max.y)),
+                    (Vector2::new(zero, -one), -T::from_single(rect.min.y)),
+                ]
+            }
+            Self::Polygon(vertices) => half_planes(&to_vectors(vertices)),
+        }
+    }
+
+    /// A point inside the region, to start searching from.
+    pub fn interior_point<T: Real>(&self) -> Vector2<T> {
+        let centre = match self {
+            Self::Box(rect) => rect.center(),
+            Self::Polygon(vertices) => {
+                let sum = vertices
+                    .iter()
+                    .fold(egui::Vec2::ZERO, |sum, v| sum + v.to_vec2());
+                (sum / vertices.len().max(1) as f32).to_pos2()
+            }
+        };
+        Vector2::new(T::from_single(centre.x), T::from_single(centre.y))
+    }
+
+    /// The smallest box that holds the whole region.
+    pub fn extent(&self) -> Rect {
+        match self {
+            Self::Box(rect) => *rect,
+            Self::Polygon(vertices) => Rect::from_points(vertices),
+        }
+    }
+
+    pub fn contains(&self, translation: Vector2<f64>) -> bool {
+        self.half_planes::<f64>()
+            .iter()
+            .all(|&(n, c)| n.dot(&translation) <= c + 1e-9 * c.abs().max(1.0))
+    }
+
+    /// The outline of the region, anti-clockwise.
+    pub fn outline(&self) -> Vec<Pos2> {
+        match self {
+            Self::Box(rect) => vec![
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ],
+            Self::Polygon(vertices) => vertices.clone(),
+        }
+    }
+}
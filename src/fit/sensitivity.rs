@@ -0,0 +1,291 @@
+use egui::{Pos2, Vec2};
+use nalgebra::Vector2;
+
+use super::{Fit, SolverKind, SolverSettings, Which, solver::solve_orientations};
+use crate::geometry::is_convex;
+
+/// How far each vertex is nudged to measure the gradient, relative to the size of the
+/// polygons. The vertices are only held in single precision, so this can't be much smaller.
+const STEP: f64 = 1e-3;
+
+/// Gradients smaller than this are taken to be round-off, rather than the margin really
+/// depending on the vertex.
+const MIN_SLOPE: f64 = 1e-9;
+
+/// How many times the distance tried is doubled when looking for where the answer flips,
+/// before giving up.
+const MAX_DOUBLINGS: usize = 8;
+
+/// How many times the distance at which the answer flips is halved down.
+const BISECTION_STEPS: usize = 16;
+
+/// How much each vertex of a polygon matters to the fit.
+#[derive(Clone, Copy, Debug)]
+pub struct VertexSensitivity {
+    /// How fast the margin grows as the vertex is moved each way. Moving the vertex along the
+    /// gradient makes the most room.
+    pub gradient: Vector2<f64>,
+    /// How far the vertex can be moved, in the direction that hurts most, before B goes from
+    /// fitting to not (or the other way round). `None` if it doesn't flip within a good
+    /// distance, or before the polygon stops being convex, or the margin doesn't depend on
+    /// this vertex at all.
+    pub tolerance: Option<f64>,
+}
+
+/// How the margin changes as each vertex of either polygon is moved on its own.
+#[derive(Clone, Debug)]
+pub struct Sensitivity {
+    /// The margin that the gradients are of.
+    pub margin: f64,
+    pub outer: Vec<VertexSensitivity>,
+    pub inner: Vec<VertexSensitivity>,
+}
+
+/// Measures how the margin (`max_min_y`, scaled to a distance) depends on every vertex of
+/// both polygons, by nudging each one in turn and solving again.
+///
+/// One vertex is measured at a time, so that the work can be spread over several frames.
+/// The tolerance of each vertex is found by moving it straight down (or up) the gradient
+/// until the solver's verdict flips, so moving it further some other way may still be safe.
+/// Nothing is skipped by the pre-checks or the polygons' symmetries, and the angles are
+/// sampled evenly, so that the margin changes smoothly as the vertices move. This means the
+/// margin may differ a little from the one the solver reports.
+pub struct SensitivityAnalysis {
+    solver: SolverKind,
+    outer_vertices: Vec<Pos2>,
+    inner_vertices: Vec<Pos2>,
+    settings: SolverSettings,
+    /// The margin with the polygons as they are, and whether B fits, or `None` if there's no
+    /// margin to measure, such as when no angle is allowed.
+    base: Option<(f64, bool)>,
+    /// How far each vertex is nudged to measure the gradient.
+    step: f64,
+    outer: Vec<VertexSensitivity>,
+    inner: Vec<VertexSensitivity>,
+}
+
+impl SensitivityAnalysis {
+    /// Gets ready to measure every vertex, solving once for the polygons as they are, which
+    /// should be centred on their centroids.
+    pub fn new(
+        solver: SolverKind,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        settings: &SolverSettings,
+    ) -> Self {
+        let settings = SolverSettings {
+            pre_checks: false,
+            exact: false,
+            verify: false,
+            adaptive: false,
+            use_symmetry: false,
+            ..settings.clone()
+        };
+        let size = outer_vertices
+            .iter()
+            .chain(inner_vertices)
+            .fold(0.0_f64, |max, v| max.max(f64::from(v.to_vec2().length())));
+        let data = solve_orientations(solver.solver(), outer_vertices, inner_vertices, &settings);
+
+        Self {
+            solver,
+            outer_vertices: outer_vertices.to_vec(),
+            inner_vertices: inner_vertices.to_vec(),
+            settings,
+            base: data
+                .margin
+                .map(|margin| (margin, matches!(data.fit, Fit::Fit { .. }))),
+            step: STEP * size.max(1.0),
+            outer: Vec::new(),
+            inner: Vec::new(),
+        }
+    }
+
+    fn num_measured(&self) -> usize {
+        self.outer.len() + self.inner.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.base.is_none()
+            || self.num_measured() >= self.outer_vertices.len() + self.inner_vertices.len()
+    }
+
+    /// How far through the vertices the analysis is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let num_vertices = self.outer_vertices.len() + self.inner_vertices.len();
+        self.num_measured() as f32 / num_vertices.max(1) as f32
+    }
+
+    /// Measures the next vertex, going through A's and then B's.
+    pub fn run_next(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+        let i = self.outer.len();
+        if i < self.outer_vertices.len() {
+            let vertex = self.vertex_sensitivity(Which::Outer, i);
+            self.outer.push(vertex);
+        } else {
+            let vertex = self.vertex_sensitivity(Which::Inner, self.inner.len());
+            self.inner.push(vertex);
+        }
+    }
+
+    /// How much every vertex matters, once they've all been measured. `None` until then, or
+    /// if there's no margin to measure.
+    pub fn sensitivity(&self) -> Option<Sensitivity> {
+        let (margin, _) = self.base?;
+        self.is_finished().then(|| Sensitivity {
+            margin,
+            outer: self.outer.clone(),
+            inner: self.inner.clone(),
+        })
+    }
+
+    /// The margin, and whether B fits, with vertex `i` of `which` polygon moved by `offset`.
+    /// `None` if that leaves the polygon not convex, or there's no margin to give.
+    fn solve_with(&self, which: Which, i: usize, offset: Vector2<f64>) -> Option<(f64, bool)> {
+        let offset = Vec2::new(offset.x as f32, offset.y as f32);
+        let (outer, inner) = match which {
+            Which::Outer => (
+                moved(&self.outer_vertices, i, offset),
+                self.inner_vertices.clone(),
+            ),
+            Which::Inner => (
+                self.outer_vertices.clone(),
+                moved(&self.inner_vertices, i, offset),
+            ),
+        };
+        if !is_convex(&outer) || !is_convex(&inner) {
+            return None;
+        }
+        let data = solve_orientations(self.solver.solver(), &outer, &inner, &self.settings);
+        Some((data.margin?, matches!(data.fit, Fit::Fit { .. })))
+    }
+
+    fn vertex_sensitivity(&self, which: Which, i: usize) -> VertexSensitivity {
+        let step = self.step;
+        let slope = |direction: Vector2<f64>| {
+            let (ahead, _) = self.solve_with(which, i, direction * step)?;
+            let (behind, _) = self.solve_with(which, i, -direction * step)?;
+            Some((ahead - behind) / (2.0 * step))
+        };
+        let gradient = Vector2::new(
+            slope(Vector2::x()).unwrap_or(0.0),
+            slope(Vector2::y()).unwrap_or(0.0),
+        );
+        let tolerance = self.base.and_then(|base| {
+            flip_distance(base, gradient, |offset| self.solve_with(which, i, offset))
+        });
+        VertexSensitivity {
+            gradient,
+            tolerance,
+        }
+    }
+}
+
+/// `vertices` with the `i`th one moved by `offset`, centred on their centroid again.
+fn moved(vertices: &[Pos2], i: usize, offset: Vec2) -> Vec<Pos2> {
+    let mut vertices = vertices.to_vec();
+    if let Some(vertex) = vertices.get_mut(i) {
+        *vertex += offset;
+    }
+    let centroid =
+        vertices.iter().fold(Vec2::ZERO, |sum, v| sum + v.to_vec2()) / vertices.len().max(1) as f32;
+    vertices.iter().map(|&v| v - centroid).collect()
+}
+
+/// How far a vertex has to move, in the direction in which the margin heads towards zero
+/// fastest, for the solver's verdict to flip. `(margin, fits)` is what the solver made of the
+/// polygons as they are, and `solve_at` what it makes of them with the vertex moved by an
+/// offset, or `None` if it can't be moved that far.
+///
+/// This starts from where the gradient says the margin would reach zero, doubling the
+/// distance until the verdict has flipped, and then halves its way back down to where it
+/// flips. Going by the verdict, rather than the sign of the margin, keeps this to the same
+/// threshold for fitting as the solver.
+fn flip_distance(
+    (margin, fits): (f64, bool),
+    gradient: Vector2<f64>,
+    solve_at: impl Fn(Vector2<f64>) -> Option<(f64, bool)>,
+) -> Option<f64> {
+    let slope = gradient.norm();
+    if slope <= MIN_SLOPE {
+        return None;
+    }
+
+    let direction = if fits { -gradient } else { gradient } / slope;
+    let flipped =
+        |distance: f64| solve_at(direction * distance).map(|(_, fits_there)| fits_there != fits);
+
+    let (mut lo, mut hi) = (0.0, margin.abs() / slope);
+    if hi <= 0.0 {
+        // Already right on the edge
+        return Some(0.0);
+    }
+    let mut doublings = 0;
+    while !flipped(hi)? {
+        if doublings == MAX_DOUBLINGS {
+            return None;
+        }
+        lo = hi;
+        hi *= 2.0;
+        doublings += 1;
+    }
+    for _ in 0..BISECTION_STEPS {
+        let mid = f64::midpoint(lo, hi);
+        // The vertex can always be moved less far than `hi`, as the places it can go that
+        // keep the polygon convex are themselves convex
+        if flipped(mid).unwrap_or(true) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Some(f64::midpoint(lo, hi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fit::test_support::{even_sweep, scalene_pair};
+
+    #[test]
+    fn tolerances_are_where_the_verdict_flips() {
+        let (outer, inner) = scalene_pair();
+        let mut analysis =
+            SensitivityAnalysis::new(SolverKind::CriticalRegion, &outer, &inner, &even_sweep());
+        let mut runs = 0;
+        while !analysis.is_finished() {
+            analysis.run_next();
+            runs += 1;
+        }
+        assert_eq!(runs, outer.len() + inner.len());
+        let sensitivity = analysis.sensitivity().expect("every angle is allowed");
+        let (_, fits) = analysis.base.expect("there is a margin");
+
+        let vertices = [
+            (Which::Outer, &sensitivity.outer),
+            (Which::Inner, &sensitivity.inner),
+        ];
+        let mut num_checked = 0;
+        for (which, vertices) in vertices {
+            for (i, vertex) in vertices.iter().enumerate() {
+                let Some(tolerance) = vertex.tolerance else {
+                    continue;
+                };
+                let gradient = vertex.gradient;
+                let direction = if fits { -gradient } else { gradient }.normalize();
+                let fits_after = |distance: f64| {
+                    analysis
+                        .solve_with(which, i, direction * distance)
+                        .map(|(_, fits)| fits)
+                };
+                assert_eq!(fits_after(0.9 * tolerance), Some(fits), "{which:?} {i}");
+                assert_eq!(fits_after(1.1 * tolerance), Some(!fits), "{which:?} {i}");
+                num_checked += 1;
+            }
+        }
+        assert!(num_checked > 0);
+    }
+}
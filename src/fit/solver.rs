@@ -0,0 +1,308 @@
+use std::f64::consts::TAU;
+
+use egui::Pos2;
+use nalgebra::Vector2;
+
+use super::{
+    ActiveConstraint, AngleDomain, Fit, FitData, OrientationComparison, PlotData, SolverSettings,
+    TranslationBound, calculate_data, feasible_intervals, min_overflow, tightest_vertices,
+};
+
+mod grid_search;
+mod linear_program;
+pub use grid_search::GridSearchSolver;
+pub use linear_program::LinearProgramSolver;
+
+/// A way of working out whether the inner polygon fits inside the outer one.
+///
+/// Every solver is given vertices centred on their centroids and defined anti-clockwise, and
+/// answers in the same form, so that they can be swapped for one another and compared.
+pub trait FitSolver {
+    fn solve(
+        &self,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        settings: &SolverSettings,
+    ) -> FitData;
+}
+
+/// Sweeps the angle, and at each one checks whether the critical region's plane passes
+/// through the positive octant. This is [`calculate_data`].
+///
+/// It only handles triangular outer polygons, and answers [`Fit::Unsupported`] for any other.
+pub struct CriticalRegionSolver;
+
+impl FitSolver for CriticalRegionSolver {
+    fn solve(
+        &self,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        settings: &SolverSettings,
+    ) -> FitData {
+        calculate_data(outer_vertices, inner_vertices, settings)
+    }
+}
+
+/// Runs `solver`, and if the settings allow B to be mirrored, runs it again on the mirrored
+/// B and keeps whichever way round leaves more room.
+///
+/// If B doesn't fit either way, this also finds where it overflows A the least.
+///
+/// B is mirrored by flipping it left to right about its centroid. Vertex numbers in the
+/// answer always refer to B as drawn.
+pub fn solve(
+    solver: &dyn FitSolver,
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    settings: &SolverSettings,
+) -> FitData {
+    let data = solve_orientations(solver, outer_vertices, inner_vertices, settings);
+    if !matches!(data.fit, Fit::NoFit) {
+        return data;
+    }
+
+    let overflow = if data.mirrored {
+        min_overflow(outer_vertices, &mirror(inner_vertices), settings)
+    } else {
+        min_overflow(outer_vertices, inner_vertices, settings)
+    };
+    FitData { overflow, ..data }
+}
+
+/// B flipped left to right about its centroid, with the order of its vertices reversed to
+/// keep them anti-clockwise.
+fn mirror(vertices: &[Pos2]) -> Vec<Pos2> {
+    vertices
+        .iter()
+        .rev()
+        .map(|v| Pos2::new(-v.x, v.y))
+        .collect()
+}
+
+/// The mirroring half of [`solve`].
+pub(super) fn solve_orientations(
+    solver: &dyn FitSolver,
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    settings: &SolverSettings,
+) -> FitData {
+    let as_drawn = solver.solve(outer_vertices, inner_vertices, settings);
+    if !settings.allow_mirroring {
+        return as_drawn;
+    }
+
+    let mirrored_vertices = mirror(inner_vertices);
+    let mut mirrored = solver.solve(outer_vertices, &mirrored_vertices, settings);
+    if let Fit::Fit {
+        active_constraints, ..
+    } = &mut mirrored.fit
+    {
+        let last = inner_vertices.len().saturating_sub(1);
+        for constraint in active_constraints {
+            constraint.vertex = last - constraint.vertex;
+        }
+    }
+
+    let orientations = OrientationComparison {
+        as_drawn: as_drawn.margin,
+        mirrored: mirrored.margin,
+    };
+    let fits = |data: &FitData| matches!(data.fit, Fit::Fit { .. });
+    let mirrored_is_better = match (fits(&as_drawn), fits(&mirrored)) {
+        (false, true) => true,
+        (true, false) => false,
+        _ => mirrored.margin > as_drawn.margin,
+    };
+    let best = if mirrored_is_better {
+        FitData {
+            mirrored: true,
+            ..mirrored
+        }
+    } else {
+        as_drawn
+    };
+
+    FitData {
+        orientations: Some(orientations),
+        ..best
+    }
+}
+
+/// The solvers that can be picked between.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum SolverKind {
+    #[default]
+    CriticalRegion,
+    LinearProgram,
+    GridSearch,
+}
+
+impl SolverKind {
+    pub const ALL: [Self; 3] = [Self::CriticalRegion, Self::LinearProgram, Self::GridSearch];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CriticalRegion => "Critical region",
+            Self::LinearProgram => "Linear program",
+            Self::GridSearch => "Grid search",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::CriticalRegion => "Sweep the angle, checking the critical region at each",
+            Self::LinearProgram => {
+                "Sweep the angle, finding the roomiest translation at each with a linear program"
+            }
+            Self::GridSearch => "Try every pose on a grid of angles and translations",
+        }
+    }
+
+    /// Whether the solver can handle an outer polygon with `num_outer_vertices` vertices.
+    pub fn supports(self, num_outer_vertices: usize) -> bool {
+        match self {
+            Self::CriticalRegion => num_outer_vertices == 3,
+            Self::LinearProgram | Self::GridSearch => true,
+        }
+    }
+
+    pub fn solver(self) -> &'static dyn FitSolver {
+        match self {
+            Self::CriticalRegion => &CriticalRegionSolver,
+            Self::LinearProgram => &LinearProgramSolver,
+            Self::GridSearch => &GridSearchSolver,
+        }
+    }
+}
+
+/// The smallest gap between any inner vertex and any outer edge, once the inner polygon has
+/// been rotated into place and then moved by `translation`.
+fn min_slack(
+    planes: &[(Vector2<f64>, f64)],
+    tightest: &[(usize, f64)],
+    translation: Vector2<f64>,
+) -> f64 {
+    planes
+        .iter()
+        .zip(tightest)
+        .map(|(&(n, _), &(_, clearance))| clearance - n.dot(&translation))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// A gap small enough to put down to round-off error, relative to the size of the outer
+/// polygon.
+fn round_off(planes: &[(Vector2<f64>, f64)]) -> f64 {
+    1e-9 * planes
+        .iter()
+        .fold(1.0, |max: f64, &(_, c)| max.max(c.abs()))
+}
+
+/// Gathers up the roomiest pose found at each angle in `xs` into a [`FitData`], where
+/// `poses` holds the translation and the smallest gap for each angle. Only the angles that
+/// the settings allow are considered for the fit.
+fn collect_poses(
+    planes: &[(Vector2<f64>, f64)],
+    inner_vertices: &[Vector2<f64>],
+    xs: Vec<f64>,
+    poses: &[(Vector2<f64>, f64)],
+    settings: &SolverSettings,
+) -> FitData {
+    let allowed = &settings.restrictions.angles;
+    let min_ys: Vec<f64> = poses.iter().map(|&(_, slack)| slack).collect();
+    let best = xs
+        .iter()
+        .zip(poses)
+        .filter(|&(&angle, _)| allowed.allows(angle))
+        .map(|(&angle, &(translation, slack))| (angle, translation, slack))
+        .max_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+    let threshold = round_off(planes);
+    let intervals = feasible_intervals(&xs, &min_ys, Some(TAU), threshold);
+    let feasible_intervals = allowed.clip(intervals, &xs, &min_ys, threshold);
+
+    let fit = match best {
+        Some((angle, translation, slack)) if slack > threshold => {
+            let bounds = settings
+                .restrictions
+                .translation
+                .as_ref()
+                .map(TranslationBound::half_planes)
+                .unwrap_or_default();
+            let (angle, translation) = settings.objective.preferred_pose(
+                angle,
+                translation,
+                &feasible_intervals,
+                Some(TAU),
+                |angle| {
+                    planes
+                        .iter()
+                        .zip(tightest_vertices(planes, inner_vertices, angle))
+                        .map(|(&(n, _), (_, clearance))| (n, clearance))
+                        .chain(bounds.iter().copied())
+                        .collect()
+                },
+            );
+            let tightest = tightest_vertices(planes, inner_vertices, angle);
+            Fit::Fit {
+                angle,
+                translation,
+                active_constraints: planes
+                    .iter()
+                    .zip(tightest)
+                    .enumerate()
+                    .map(|(edge, (&(n, _), (vertex, clearance)))| ActiveConstraint {
+                        edge,
+                        vertex,
+                        slack: clearance - n.dot(&translation),
+                    })
+                    .collect(),
+            }
+        }
+        _ => Fit::NoFit,
+    };
+    FitData {
+        fit,
+        plot_data: PlotData {
+            xs,
+            yss: Vec::new(),
+            slacks: min_ys.clone(),
+            min_ys,
+            translations: poses.iter().map(|&(translation, _)| translation).collect(),
+            domain: AngleDomain::full_turn(),
+        },
+        feasible_intervals,
+        decided_by: None,
+        exact_check: None,
+        certificate: None,
+        margin: best.map(|(_, _, slack)| slack),
+        max_clearance: best.map(|(_, _, slack)| slack + settings.clearance),
+        mirrored: false,
+        orientations: None,
+        overflow: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fit::test_support::centred;
+
+    #[test]
+    fn answers_unsupported_only_where_the_kind_says_so() {
+        let square = centred(&[(0.0, 0.0), (40.0, 0.0), (40.0, 40.0), (0.0, 40.0)]);
+        let triangle = centred(&[(0.0, 0.0), (10.0, 0.0), (5.0, 8.0)]);
+        let settings = SolverSettings {
+            allow_mirroring: true,
+            ..SolverSettings::default()
+        };
+        for kind in SolverKind::ALL {
+            let data = solve(kind.solver(), &square, &triangle, &settings);
+            assert_eq!(
+                matches!(data.fit, Fit::Unsupported),
+                !kind.supports(square.len()),
+                "{}",
+                kind.name()
+            );
+        }
+    }
+}
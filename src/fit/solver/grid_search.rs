@@ -0,0 +1,97 @@
+use egui::Pos2;
+use itertools::Itertools as _;
+use nalgebra::Vector2;
+
+use super::{FitSolver, collect_poses, min_slack, tightest_vertices};
+use crate::fit::{FitData, SolverSettings, inset_half_planes, to_vectors};
+
+/// How many angles to try, evenly spread over a whole turn.
+const NUM_ANGLES: usize = 120;
+/// How many translations to try along each side of the outer polygon's bounding box.
+const GRID_SIZE: usize = 11;
+/// How many times to zoom in on the best translation found so far.
+const REFINEMENTS: usize = 6;
+
+/// Tries every pose on a grid of angles and translations, keeping whichever leaves the most
+/// room, then zooms in around the best translation at each angle.
+///
+/// This makes no assumptions about the shapes at all, so it's a useful check on the cleverer
+/// solvers, but it can only ever underestimate how much room there is.
+pub struct GridSearchSolver;
+
+impl FitSolver for GridSearchSolver {
+    fn solve(
+        &self,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        settings: &SolverSettings,
+    ) -> FitData {
+        let outer = to_vectors(outer_vertices);
+        let planes = inset_half_planes(&outer, settings.clearance);
+        let inner = to_vectors(inner_vertices);
+        let restrictions = &settings.restrictions;
+
+        // Search the whole of A, or just the region the translation is bounded to
+        let (min, max) = match &restrictions.translation {
+            Some(bound) => {
+                let extent = bound.extent();
+                (to_vector(extent.min), to_vector(extent.max))
+            }
+            None => outer.iter().fold(
+                (
+                    Vector2::repeat(f64::INFINITY),
+                    Vector2::repeat(f64::NEG_INFINITY),
+                ),
+                |(min, max), v| (min.inf(v), max.sup(v)),
+            ),
+        };
+        let centre = (min + max) / 2.0;
+        let half_size = (max - min) / 2.0;
+        let allowed = |t: &Vector2<f64>| {
+            restrictions
+                .translation
+                .as_ref()
+                .is_none_or(|bound| bound.contains(*t))
+        };
+
+        let xs = restrictions.angles.sweep(NUM_ANGLES);
+        let poses: Vec<(Vector2<f64>, f64)> = xs
+            .iter()
+            .map(|&angle| {
+                let tightest = tightest_vertices(&planes, &inner, angle);
+                let start_slack = if allowed(&centre) {
+                    min_slack(&planes, &tightest, centre)
+                } else {
+                    f64::NEG_INFINITY
+                };
+                let mut best = (centre, start_slack);
+                let mut half_size = half_size;
+                for _ in 0..=REFINEMENTS {
+                    best = grid(best.0, half_size)
+                        .filter(allowed)
+                        .map(|t| (t, min_slack(&planes, &tightest, t)))
+                        .fold(best, |best, pose| if pose.1 > best.1 { pose } else { best });
+                    // Zoom in on the best point so far, keeping its neighbours in view
+                    half_size *= 2.0 / (GRID_SIZE - 1) as f64;
+                }
+                best
+            })
+            .collect();
+
+        collect_poses(&planes, &inner, xs, &poses, settings)
+    }
+}
+
+fn to_vector(point: Pos2) -> Vector2<f64> {
+    Vector2::new(f64::from(point.x), f64::from(point.y))
+}
+
+/// A square grid of `GRID_SIZE` by `GRID_SIZE` points, centred on `centre` and reaching
+/// `half_size` either side of it.
+fn grid(centre: Vector2<f64>, half_size: Vector2<f64>) -> impl Iterator<Item = Vector2<f64>> {
+    let steps = (0..GRID_SIZE).map(|i| 2.0 * i as f64 / (GRID_SIZE - 1) as f64 - 1.0);
+    steps
+        .clone()
+        .cartesian_product(steps)
+        .map(move |(u, v)| centre + Vector2::new(u * half_size.x, v * half_size.y))
+}
@@ -0,0 +1,63 @@
+use egui::Pos2;
+use itertools::Itertools as _;
+use nalgebra::Vector2;
+
+use super::{FitSolver, collect_poses, tightest_vertices};
+use crate::fit::{
+    FitData, SolverSettings, TranslationBound, inset_half_planes, max_min_slack_within, to_vectors,
+};
+
+/// How many angles to try, evenly spread over a whole turn.
+const NUM_ANGLES: usize = 400;
+
+/// Sweeps the angle, and at each one finds the translation that leaves the most room
+/// between the inner polygon and the outer one's edges.
+///
+/// That's a linear program in `(t.x, t.y, s)`: maximise `s` subject to `n.dot(t) + s <= g`
+/// for each outer edge, where `g` is the clearance of the inner vertex closest to that edge.
+/// See [`max_min_slack`]. Unlike the critical region method, it works for outer polygons
+/// with any number of sides.
+pub struct LinearProgramSolver;
+
+impl FitSolver for LinearProgramSolver {
+    fn solve(
+        &self,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        settings: &SolverSettings,
+    ) -> FitData {
+        let planes = inset_half_planes(&to_vectors(outer_vertices), settings.clearance);
+        let inner = to_vectors(inner_vertices);
+        let restrictions = &settings.restrictions;
+
+        let xs = restrictions.angles.sweep(NUM_ANGLES);
+        let poses: Vec<(Vector2<f64>, f64)> = xs
+            .iter()
+            .map(|&angle| {
+                let tightest = tightest_vertices(&planes, &inner, angle);
+                best_translation(&planes, &tightest, restrictions.translation.as_ref())
+            })
+            .collect();
+
+        collect_poses(&planes, &inner, xs, &poses, settings)
+    }
+}
+
+/// The translation (within `bound`, if there is one) that maximises the smallest gap to any
+/// edge, and that gap.
+fn best_translation(
+    planes: &[(Vector2<f64>, f64)],
+    tightest: &[(usize, f64)],
+    bound: Option<&TranslationBound>,
+) -> (Vector2<f64>, f64) {
+    let constraints = planes
+        .iter()
+        .zip(tightest)
+        .map(|(&(n, _), &(_, clearance))| (n, clearance))
+        .collect_vec();
+    let (bounds, origin) = bound.map_or_else(
+        || (Vec::new(), Vector2::zeros()),
+        |bound| (bound.half_planes(), bound.interior_point()),
+    );
+    max_min_slack_within(&constraints, &bounds, origin).unwrap_or((origin, f64::NEG_INFINITY))
+}
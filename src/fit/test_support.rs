@@ -0,0 +1,46 @@
+//! Polygons and settings shared by the solvers' tests.
+
+use egui::{Pos2, pos2};
+
+use super::SolverSettings;
+
+/// `points` as vertices centred on their centroid.
+pub fn centred(points: &[(f32, f32)]) -> Vec<Pos2> {
+    let n = points.len() as f32;
+    let (sum_x, sum_y) = points
+        .iter()
+        .fold((0.0, 0.0), |(sum_x, sum_y), &(x, y)| (sum_x + x, sum_y + y));
+    points
+        .iter()
+        .map(|&(x, y)| pos2(x - sum_x / n, y - sum_y / n))
+        .collect()
+}
+
+/// A scalene A, and a scalene B that only fits at a few ranges of angles, none of which are
+/// mirror images of one another. Any mix-up between an angle and its negative shows up here.
+pub fn scalene_pair() -> (Vec<Pos2>, Vec<Pos2>) {
+    let outer = centred(&[(-60.0, -40.0), (70.0, -30.0), (-10.0, 60.0)]);
+    let inner = centred(&[(-57.0, -15.2), (53.2, -7.6), (9.5, 22.8)]);
+    (outer, inner)
+}
+
+/// [`scalene_pair`] with B turned through about 1.5 radians, so that it first fits well away
+/// from zero and no range of angles that fits is its own mirror image. The proof has to rule
+/// out a good stretch of angles before it finds one that fits, and an angle mixed up with its
+/// negative lands outside every range that does.
+pub fn turned_scalene_pair() -> (Vec<Pos2>, Vec<Pos2>) {
+    let (outer, _) = scalene_pair();
+    let inner = centred(&[(11.1, -57.9), (11.3, 52.5), (-22.1, 11.1)]);
+    (outer, inner)
+}
+
+/// Settings that sample the angles evenly over a whole turn, with nothing skipped, so that
+/// the curves can be compared point by point.
+pub fn even_sweep() -> SolverSettings {
+    SolverSettings {
+        pre_checks: false,
+        adaptive: false,
+        use_symmetry: false,
+        ..SolverSettings::default()
+    }
+}
@@ -0,0 +1,402 @@
+use std::{
+    f64::consts::{FRAC_PI_2, TAU},
+    ops::{Add, Mul, Sub},
+};
+
+use egui::Pos2;
+use itertools::Itertools as _;
+
+/// How many equal pieces the angles are cut into before any of them are subdivided.
+const INITIAL_PIECES: usize = 32;
+
+/// How far `f64::cos` and `f64::sin` are allowed to stray from the true value. They're
+/// accurate to within an ulp or so, and an ulp of a number no bigger than 1 is far smaller
+/// than this.
+const TRIG_PADDING: f64 = 1e-15;
+
+/// A closed range of reals, `[lo, hi]`, that is guaranteed to contain some true value.
+///
+/// Every operation rounds its result outwards, so the guarantee survives floating point.
+#[derive(Clone, Copy, Debug)]
+struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    fn point(x: f64) -> Self {
+        Self { lo: x, hi: x }
+    }
+
+    fn widened(lo: f64, hi: f64) -> Self {
+        Self {
+            lo: lo.next_down(),
+            hi: hi.next_up(),
+        }
+    }
+
+    /// An interval containing the smaller of any value from `self` and any from `other`.
+    fn min(self, other: Self) -> Self {
+        Self {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.min(other.hi),
+        }
+    }
+
+    /// Bounds `f` over `[start, end]`, where `f` is `cos` shifted so that it peaks at `peak`.
+    fn of_trig(f: fn(f64) -> f64, peak: f64, start: f64, end: f64) -> Self {
+        // Whether `start <= x + 2kπ <= end` for some integer `k`, erring on the side of yes
+        let hits = |x: f64| {
+            let first = ((start - x) / TAU - 1e-9).ceil();
+            let last = ((end - x) / TAU + 1e-9).floor();
+            first <= last
+        };
+        let (f_start, f_end) = (f(start), f(end));
+        let hi = if hits(peak) { 1.0 } else { f_start.max(f_end) };
+        let lo = if hits(peak + TAU / 2.0) {
+            -1.0
+        } else {
+            f_start.min(f_end)
+        };
+        Self {
+            lo: (lo - TRIG_PADDING).max(-1.0),
+            hi: (hi + TRIG_PADDING).min(1.0),
+        }
+    }
+
+    fn cos(start: f64, end: f64) -> Self {
+        Self::of_trig(f64::cos, 0.0, start, end)
+    }
+
+    fn sin(start: f64, end: f64) -> Self {
+        Self::of_trig(f64::sin, FRAC_PI_2, start, end)
+    }
+}
+
+impl Add for Interval {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::widened(self.lo + other.lo, self.hi + other.hi)
+    }
+}
+
+impl Sub for Interval {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::widened(self.lo - other.hi, self.hi - other.lo)
+    }
+}
+
+impl Mul for Interval {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let products = [
+            self.lo * other.lo,
+            self.lo * other.hi,
+            self.hi * other.lo,
+            self.hi * other.hi,
+        ];
+        let lo = products.into_iter().fold(f64::INFINITY, f64::min);
+        let hi = products.into_iter().fold(f64::NEG_INFINITY, f64::max);
+        Self::widened(lo, hi)
+    }
+}
+
+/// A pair of interval coordinates.
+#[derive(Clone, Copy)]
+struct Interval2 {
+    x: Interval,
+    y: Interval,
+}
+
+impl Interval2 {
+    fn from_pos(p: Pos2) -> Self {
+        Self {
+            x: Interval::point(f64::from(p.x)),
+            y: Interval::point(f64::from(p.y)),
+        }
+    }
+
+    fn dot(self, other: Self) -> Interval {
+        self.x * other.x + self.y * other.y
+    }
+
+    fn cross(self, other: Self) -> Interval {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+/// What the interval sweep managed to prove.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    /// The minimum curve is provably positive at `angle`, so B fits inside A there.
+    ProvenFit { angle: f64 },
+    /// The minimum curve is provably negative at every angle, so B can't fit inside A.
+    ProvenNoFit,
+    /// Neither could be shown without cutting the angles up more finely.
+    Undecided,
+}
+
+impl Verdict {
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ProvenFit { .. } => "proven fit",
+            Self::ProvenNoFit => "proven no fit",
+            Self::Undecided => "undecided at this resolution",
+        }
+    }
+}
+
+/// Proven bounds on the minimum curve over every angle from `start` to `end` (in radians).
+///
+/// The curve here uses unnormalised edge normals, so it is a positive multiple of the one
+/// that's plotted: its sign means the same thing, but its size doesn't.
+#[derive(Clone, Copy, Debug)]
+pub struct CertificateEntry {
+    pub start: f64,
+    pub end: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// The evidence for a [`Verdict`].
+///
+/// For [`Verdict::ProvenNoFit`], the entries cover every angle from 0 to 2π and each has a
+/// negative `upper`. For [`Verdict::ProvenFit`], the last entry is the single angle at which
+/// the curve has a positive `lower`. Otherwise, the entries with a non-negative `upper` are
+/// the ones that couldn't be ruled out.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    pub verdict: Verdict,
+    pub entries: Vec<CertificateEntry>,
+}
+
+impl Certificate {
+    /// The certificate as plain text, with every number written out in full so that it can
+    /// be checked independently.
+    pub fn to_text(&self) -> String {
+        let header = format!("# {}\n# start end lower upper", self.verdict.description());
+        let lines = self.entries.iter().map(|entry| {
+            format!(
+                "{:e} {:e} {:e} {:e}",
+                entry.start, entry.end, entry.lower, entry.upper
+            )
+        });
+        std::iter::once(header).chain(lines).join("\n")
+    }
+}
+
+/// The outer polygon's edges and the inner polygon's vertices, as intervals.
+struct Curves {
+    /// Unnormalised outward normal and offset of each outer edge.
+    planes: Vec<(Interval2, Interval)>,
+    /// The (non-negative) normal to the critical plane.
+    n_p: [Interval; 3],
+    inner_vertices: Vec<Interval2>,
+}
+
+impl Curves {
+    fn new(outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> Option<Self> {
+        let outer = outer_vertices
+            .iter()
+            .map(|&p| Interval2::from_pos(p))
+            .collect_vec();
+        let planes = outer
+            .last()
+            .into_iter()
+            .chain(&outer)
+            .tuple_windows()
+            .map(|(&from, &to)| {
+                let n = Interval2 {
+                    x: to.y - from.y,
+                    y: from.x - to.x,
+                };
+                (n, n.dot(to))
+            })
+            .collect_vec();
+        let &[(n_1, _), (n_2, _), (n_3, _)] = planes.as_slice() else {
+            return None;
+        };
+        let n_p = [n_2.cross(n_3), n_3.cross(n_1), n_1.cross(n_2)];
+        // Splitting the minimum edge by edge relies on these all being non-negative
+        if n_p.iter().any(|n| n.lo < 0.0) {
+            return None;
+        }
+
+        Some(Self {
+            planes,
+            n_p,
+            inner_vertices: inner_vertices
+                .iter()
+                .map(|&p| Interval2::from_pos(p))
+                .collect(),
+        })
+    }
+
+    /// Bounds the minimum curve over all angles whose cosine and sine lie in `cos` and `sin`.
+    ///
+    /// Because every element of `n_p` is non-negative, the minimum over all the index
+    /// choices is the sum over the edges of the smallest clearance for each.
+    fn bound(&self, cos: Interval, sin: Interval) -> Interval {
+        self.planes
+            .iter()
+            .zip(self.n_p)
+            .filter_map(|(&(n, c), n_p_i)| {
+                let min_clearance = self
+                    .inner_vertices
+                    .iter()
+                    .map(|&b| c - (n.dot(b) * cos + n.cross(b) * sin))
+                    .reduce(Interval::min)?;
+                Some(n_p_i * min_clearance)
+            })
+            .fold(Interval::point(0.0), |sum, term| sum + term)
+    }
+
+    fn bound_over(&self, start: f64, end: f64) -> Interval {
+        self.bound(Interval::cos(start, end), Interval::sin(start, end))
+    }
+}
+
+/// Tries to prove whether the inner polygon fits inside the outer one, by cutting the angles
+/// into pieces and bounding the minimum curve over each piece with interval arithmetic.
+///
+/// Pieces that can't be ruled out are halved, at most `max_depth` times. Returns `None` if
+/// the outer polygon isn't a triangle defined anti-clockwise.
+pub fn verify(
+    outer_vertices: &[Pos2],
+    inner_vertices: &[Pos2],
+    max_depth: u32,
+) -> Option<Certificate> {
+    let curves = Curves::new(outer_vertices, inner_vertices)?;
+    let entry = |start: f64, end: f64, bound: Interval| CertificateEntry {
+        start,
+        end,
+        lower: bound.lo,
+        upper: bound.hi,
+    };
+
+    // The last piece ends just past the nearest f64 to 2π, so that the pieces definitely
+    // cover a whole turn
+    let cuts = (0..INITIAL_PIECES)
+        .map(|i| TAU * i as f64 / INITIAL_PIECES as f64)
+        .chain([TAU.next_up()])
+        .collect_vec();
+    // Worked through in order of angle, so the certificate comes out sorted
+    let mut pieces = cuts
+        .iter()
+        .tuple_windows()
+        .map(|(&start, &end)| (start, end, 0))
+        .collect_vec();
+    pieces.reverse();
+
+    let mut entries = Vec::new();
+    let mut proven_no_fit = true;
+    while let Some((start, end, depth)) = pieces.pop() {
+        let bound = curves.bound_over(start, end);
+        if bound.hi < 0.0 {
+            entries.push(entry(start, end, bound));
+            continue;
+        }
+
+        let mid = start + (end - start) / 2.0;
+        let at_mid = curves.bound(Interval::cos(mid, mid), Interval::sin(mid, mid));
+        if at_mid.lo > 0.0 {
+            entries.push(entry(mid, mid, at_mid));
+            return Some(Certificate {
+                verdict: Verdict::ProvenFit { angle: mid },
+                entries,
+            });
+        }
+
+        if depth < max_depth {
+            pieces.push((mid, end, depth + 1));
+            pieces.push((start, mid, depth + 1));
+        } else {
+            entries.push(entry(start, end, bound));
+            proven_no_fit = false;
+        }
+    }
+
+    Some(Certificate {
+        verdict: if proven_no_fit {
+            Verdict::ProvenNoFit
+        } else {
+            Verdict::Undecided
+        },
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fit::{
+        CriticalRegion, calculate_data, half_planes,
+        test_support::{even_sweep, turned_scalene_pair},
+        to_vectors,
+    };
+
+    /// Whether `angle`, or the same angle a turn on, lies inside one of `intervals`.
+    fn inside(intervals: &[(f64, f64)], angle: f64) -> bool {
+        [angle, angle + TAU].into_iter().any(|angle| {
+            intervals
+                .iter()
+                .any(|&(start, end)| start < angle && angle < end)
+        })
+    }
+
+    #[test]
+    fn proves_a_fit_where_the_float_curve_has_room() {
+        let (outer, inner) = turned_scalene_pair();
+        let certificate = verify(&outer, &inner, 12).expect("A is a triangle");
+        let Verdict::ProvenFit { angle } = certificate.verdict else {
+            panic!("expected a proven fit, got {:?}", certificate.verdict);
+        };
+
+        let region =
+            CriticalRegion::<f64>::new(half_planes(&to_vectors(&outer)), to_vectors(&inner))
+                .expect("A is a triangle");
+        let sample = region.sample(angle).expect("there are critical planes");
+        assert!(sample.min_y > 0.0, "{} at {angle}", sample.min_y);
+    }
+
+    #[test]
+    fn lines_up_with_the_feasible_intervals() {
+        let (outer, inner) = turned_scalene_pair();
+        let intervals = calculate_data(&outer, &inner, &even_sweep())
+            .feasible_intervals
+            .iter()
+            .map(|interval| (interval.start, interval.end))
+            .collect_vec();
+        let certificate = verify(&outer, &inner, 12).expect("A is a triangle");
+        let Verdict::ProvenFit { angle } = certificate.verdict else {
+            panic!("expected a proven fit, got {:?}", certificate.verdict);
+        };
+        assert!(inside(&intervals, angle), "{angle} not in {intervals:?}");
+
+        // Everything before the proven angle has been ruled out, so none of it can be in a
+        // feasible interval, short of the few samples' slack at the ends
+        let ruled_out = certificate
+            .entries
+            .iter()
+            .filter(|entry| entry.upper < 0.0)
+            .collect_vec();
+        assert!(!ruled_out.is_empty());
+        for entry in ruled_out {
+            for x in [
+                entry.start,
+                f64::midpoint(entry.start, entry.end),
+                entry.end,
+            ] {
+                let slack = 0.02;
+                assert!(
+                    !inside(&intervals, x + slack) || !inside(&intervals, x - slack),
+                    "{x} was ruled out but is in {intervals:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use std::f32::consts::PI;
+
+use egui::{Pos2, Vec2, emath};
+use itertools::Itertools as _;
+use nalgebra::{RealField, Vector2};
+
+/// Computes the outward unit normal `n` and offset `c` of each edge of a polygon, so that a
+/// point `p` lies inside the polygon when `n.dot(p) <= c` for every edge.
+///
+/// Edge `i` is the one that ends at vertex `i`.
+pub fn edge_half_planes(vertices: &[Pos2]) -> Vec<(Vec2, f32)> {
+    let vertices = vertices
+        .iter()
+        .map(|v| Vector2::new(v.x, v.y))
+        .collect_vec();
+    half_planes(&vertices)
+        .into_iter()
+        .map(|(n, c)| (Vec2::new(n.x, n.y), c))
+        .collect()
+}
+
+/// [`edge_half_planes`], in whatever precision `vertices` are in, as the solver needs.
+pub fn half_planes<T: RealField + Copy>(vertices: &[Vector2<T>]) -> Vec<(Vector2<T>, T)> {
+    vertices
+        .last()
+        .into_iter()
+        .chain(vertices)
+        .tuple_windows()
+        .map(|(from, to)| {
+            let edge = to - from;
+            // Compute the normal by rotating the edge clockwise 90 degrees and normalising
+            // This works because we have defined the polygon to have its vertices defined
+            // anti-clockwise
+            // TODO: Make this method robust by detecting which way round the vertices are
+            // defined
+            let n = Vector2::new(edge.y, -edge.x).normalize();
+            let c = n.dot(to);
+            (n, c)
+        })
+        .collect()
+}
+
+/// Each edge of a polygon as a `(from, to)` pair, starting with the edge that ends at the
+/// first vertex.
+pub fn edges(vertices: &[Pos2]) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+    vertices
+        .last()
+        .into_iter()
+        .chain(vertices)
+        .copied()
+        .tuple_windows()
+}
+
+/// The area of a polygon, which is positive when its vertices are defined anti-clockwise.
+pub fn signed_area(vertices: &[Pos2]) -> f32 {
+    edges(vertices)
+        .map(|(from, to)| from.x * to.y - to.x * from.y)
+        .sum::<f32>()
+        / 2.0
+}
+
+pub fn perimeter(vertices: &[Pos2]) -> f32 {
+    edges(vertices).map(|(from, to)| from.distance(to)).sum()
+}
+
+/// The centre of mass of the polygon's area, which in general is not the same as the
+/// average of its vertices.
+pub fn area_centroid(vertices: &[Pos2]) -> Pos2 {
+    let area = signed_area(vertices);
+    if area.abs() <= f32::EPSILON {
+        return vertex_centroid(vertices);
+    }
+
+    let sum = edges(vertices).fold(Vec2::ZERO, |sum, (from, to)| {
+        let cross = from.x * to.y - to.x * from.y;
+        sum + (from.to_vec2() + to.to_vec2()) * cross
+    });
+    (sum / (6.0 * area)).to_pos2()
+}
+
+/// The average of the vertices.
+pub fn vertex_centroid(vertices: &[Pos2]) -> Pos2 {
+    let sum = vertices.iter().fold(Vec2::ZERO, |sum, v| sum + v.to_vec2());
+    (sum / vertices.len().max(1) as f32).to_pos2()
+}
+
+/// The narrowest gap between two parallel lines that the polygon can pass through, and the
+/// direction (perpendicular to those lines) in which it's that narrow.
+///
+/// For a convex polygon the narrowest direction is always perpendicular to one of its edges,
+/// so only those directions are checked.
+pub fn min_width(vertices: &[Pos2]) -> (f32, Vec2) {
+    edge_half_planes(vertices)
+        .into_iter()
+        .map(|(n, c)| {
+            let width = vertices
+                .iter()
+                .map(|v| c - n.dot(v.to_vec2()))
+                .fold(0.0, f32::max);
+            (width, n)
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .unwrap_or((0.0, Vec2::X))
+}
+
+/// The greatest distance between any two vertices.
+pub fn diameter(vertices: &[Pos2]) -> f32 {
+    vertices
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| a.distance(*b))
+        .fold(0.0, f32::max)
+}
+
+/// The interior angle at each vertex, in radians.
+pub fn interior_angles(vertices: &[Pos2]) -> Vec<f32> {
+    let edges = edges(vertices).collect_vec();
+    // The edge ending at each vertex, alongside the edge starting from it
+    edges
+        .iter()
+        .zip(edges.iter().cycle().skip(1))
+        .map(|(&(from, vertex), &(_, to))| {
+            let turn = (vertex - from).angle() - (to - vertex).angle();
+            // Wrap the turn into (-π, π], then it's the exterior angle
+            let turn = (turn + PI).rem_euclid(2.0 * PI) - PI;
+            PI + turn
+        })
+        .collect()
+}
+
+/// Whether the polygon is convex, with its vertices defined anti-clockwise, so that it
+/// turns left (or carries straight on) at every vertex.
+pub fn is_convex(vertices: &[Pos2]) -> bool {
+    vertices
+        .iter()
+        .circular_tuple_windows()
+        .all(|(&from, &vertex, &to)| {
+            let (incoming, outgoing) = (vertex - from, to - vertex);
+            incoming.x * outgoing.y - outgoing.x * incoming.y >= 0.0
+        })
+}
+
+/// The smallest convex polygon containing all of `points`, with its vertices defined
+/// anti-clockwise. Points along its edges are left out.
+///
+/// This is Andrew's monotone chain: the lower and upper halves of the hull are each built
+/// up from the points sorted left to right, dropping any point that would make them turn
+/// the wrong way.
+pub fn convex_hull(points: &[Pos2]) -> Vec<Pos2> {
+    let sorted = points
+        .iter()
+        .copied()
+        .sorted_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)))
+        .dedup()
+        .collect_vec();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let turns_left = |a: Pos2, b: Pos2, c: Pos2| {
+        let (ab, bc) = (b - a, c - b);
+        ab.x * bc.y - bc.x * ab.y > 0.0
+    };
+    let half = |points: &mut dyn Iterator<Item = Pos2>| {
+        let mut chain: Vec<Pos2> = Vec::new();
+        for point in points {
+            while let [.., a, b] = chain.as_slice()
+                && !turns_left(*a, *b, point)
+            {
+                chain.pop();
+            }
+            chain.push(point);
+        }
+        // The last point starts the other half
+        chain.pop();
+        chain
+    };
+    let mut hull = half(&mut sorted.iter().copied());
+    hull.extend(half(&mut sorted.iter().rev().copied()));
+    hull
+}
+
+/// Cuts away the part of a convex polygon where `outside` is positive (Sutherland-Hodgman
+/// clipping), leaving the part on the near side of a line.
+///
+/// `outside` is how far a point lies past the line, such as `n.dot(p) - c`, and
+/// `lerp(a, b, t)` is the point a fraction `t` of the way from `a` to `b`. This way the same
+/// clipping works for screen points as well as for the solver's vectors.
+pub fn clip_to_half_plane<P, T>(
+    vertices: &[P],
+    outside: impl Fn(P) -> T,
+    lerp: impl Fn(P, P, T) -> P,
+) -> Vec<P>
+where
+    P: Copy,
+    T: num_traits::Num + PartialOrd + Copy,
+{
+    let mut clipped = Vec::with_capacity(vertices.len() + 1);
+    for (from, to) in vertices
+        .last()
+        .into_iter()
+        .chain(vertices)
+        .copied()
+        .tuple_windows()
+    {
+        let (from_outside, to_outside) = (outside(from), outside(to));
+        if (from_outside > T::zero()) != (to_outside > T::zero()) {
+            clipped.push(lerp(from, to, from_outside / (from_outside - to_outside)));
+        }
+        if to_outside <= T::zero() {
+            clipped.push(to);
+        }
+    }
+    clipped
+}
+
+/// The radius of the largest circle that fits inside a convex polygon, and its centre.
+///
+/// Found by pushing every edge inwards until there's (almost) nothing left of the polygon.
+pub fn inscribed_circle(vertices: &[Pos2]) -> (f32, Pos2) {
+    let planes = edge_half_planes(vertices);
+    let shrunk = |r: f32| {
+        planes.iter().fold(vertices.to_vec(), |polygon, &(n, c)| {
+            clip_to_half_plane(
+                &polygon,
+                |p| n.dot(p.to_vec2()) - (c - r),
+                |a, b, t| a.lerp(b, t),
+            )
+        })
+    };
+
+    let mut lo = 0.0;
+    let mut hi = diameter(vertices) / 2.0;
+    for _ in 0..40 {
+        let mid = f32::midpoint(lo, hi);
+        if shrunk(mid).is_empty() {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    let centre = vertex_centroid(&shrunk(lo));
+    (lo, centre)
+}
+
+/// The radius of the smallest circle containing the polygon, and its centre.
+///
+/// The circle is always determined by two or three of the vertices, so this tries all of
+/// them, which is plenty fast enough for the handful of vertices we deal with.
+pub fn enclosing_circle(vertices: &[Pos2]) -> (f32, Pos2) {
+    let contains_all = |(radius, centre): (f32, Pos2)| {
+        let tolerance = 1e-4 * radius.max(1.0);
+        vertices
+            .iter()
+            .all(|v| v.distance(centre) <= radius + tolerance)
+    };
+
+    let from_pairs = vertices
+        .iter()
+        .tuple_combinations()
+        .map(|(&a, &b)| (a.distance(b) / 2.0, a.lerp(b, 0.5)));
+    let from_triples = vertices
+        .iter()
+        .tuple_combinations()
+        .filter_map(|(&a, &b, &c)| circumcircle(a, b, c));
+
+    from_pairs
+        .chain(from_triples)
+        .filter(|&circle| contains_all(circle))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .unwrap_or((0.0, vertex_centroid(vertices)))
+}
+
+/// The circle through three points, unless they're in a straight line.
+fn circumcircle(a: Pos2, b: Pos2, c: Pos2) -> Option<(f32, Pos2)> {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * (ab.x * ac.y - ab.y * ac.x);
+    if d.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let offset = Vec2::new(
+        ac.y * ab.length_sq() - ab.y * ac.length_sq(),
+        ab.x * ac.length_sq() - ac.x * ab.length_sq(),
+    ) / d;
+    Some((offset.length(), a + offset))
+}
+
+/// The symmetries of a polygon: rotations about its centroid and reflections in lines
+/// through it that leave the polygon looking the same.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symmetry {
+    /// The polygon looks the same after turning through any multiple of 2π divided by this.
+    pub order: usize,
+    /// The angle (in [0, π), measured from the x axis) of each line the polygon can be
+    /// reflected in.
+    pub mirror_axes: Vec<f32>,
+}
+
+impl Symmetry {
+    /// The name of the symmetry group: cyclic (`C`) without mirror lines, dihedral (`D`)
+    /// with them.
+    pub fn group_name(&self) -> String {
+        let letter = if self.mirror_axes.is_empty() {
+            'C'
+        } else {
+            'D'
+        };
+        format!("{letter}{}", self.order)
+    }
+}
+
+/// Whether every one of `moved` lies within `tolerance` of one of `vertices`.
+fn same_points(vertices: &[Pos2], mut moved: impl Iterator<Item = Pos2>, tolerance: f32) -> bool {
+    moved.all(|p| vertices.iter().any(|v| v.distance(p) <= tolerance))
+}
+
+/// Finds the symmetries of a polygon, allowing its vertices to be out by a small fraction
+/// of its diameter.
+///
+/// The order of rotational symmetry must divide the number of vertices, so only those
+/// divisors are tried. Mirror lines of a polygon pass through its centroid and either a
+/// vertex or the middle of an edge, so only those lines are tried.
+pub fn symmetry(vertices: &[Pos2]) -> Symmetry {
+    let tolerance = 1e-3 * diameter(vertices);
+    let centre = area_centroid(vertices);
+    let n = vertices.len();
+
+    let order = (1..=n)
+        .rev()
+        .filter(|k| n % k == 0)
+        .find(|&k| {
+            let rotation = emath::Rot2::from_angle(2.0 * PI / k as f32);
+            let rotated = vertices.iter().map(|&v| centre + rotation * (v - centre));
+            same_points(vertices, rotated, tolerance)
+        })
+        .unwrap_or(1);
+
+    let candidates = vertices
+        .iter()
+        .copied()
+        .chain(edges(vertices).map(|(from, to)| from.lerp(to, 0.5)))
+        .filter(|&p| p.distance(centre) > tolerance)
+        .map(|p| (p - centre).angle().rem_euclid(PI));
+    let mut mirror_axes: Vec<f32> = Vec::new();
+    for axis in candidates {
+        let is_new = mirror_axes.iter().all(|&other| {
+            let difference = (axis - other).rem_euclid(PI);
+            difference.min(PI - difference) > 1e-3
+        });
+        let direction = Vec2::angled(axis);
+        let reflected = vertices.iter().map(|&v| {
+            let offset = v - centre;
+            centre + 2.0 * offset.dot(direction) * direction - offset
+        });
+        if is_new && same_points(vertices, reflected, tolerance) {
+            mirror_axes.push(axis);
+        }
+    }
+
+    Symmetry { order, mirror_axes }
+}
+
+/// Measurements of a convex polygon that are useful when comparing shapes.
+#[derive(Clone, Debug)]
+pub struct PolygonProperties {
+    pub area: f32,
+    pub perimeter: f32,
+    pub centroid: Pos2,
+    pub min_width: f32,
+    /// The unit direction in which the polygon is narrowest.
+    pub min_width_direction: Vec2,
+    pub diameter: f32,
+    pub inradius: f32,
+    pub incentre: Pos2,
+    pub circumradius: f32,
+    pub circumcentre: Pos2,
+    /// In radians, one for each vertex.
+    pub interior_angles: Vec<f32>,
+    pub symmetry: Symmetry,
+}
+
+impl PolygonProperties {
+    pub fn of(vertices: &[Pos2]) -> Self {
+        let (min_width, min_width_direction) = min_width(vertices);
+        let (inradius, incentre) = inscribed_circle(vertices);
+        let (circumradius, circumcentre) = enclosing_circle(vertices);
+        Self {
+            area: signed_area(vertices),
+            perimeter: perimeter(vertices),
+            centroid: area_centroid(vertices),
+            min_width,
+            min_width_direction,
+            diameter: diameter(vertices),
+            inradius,
+            incentre,
+            circumradius,
+            circumcentre,
+            interior_angles: interior_angles(vertices),
+            symmetry: symmetry(vertices),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use egui::pos2;
+
+    use super::*;
+
+    /// A regular polygon with `n` vertices on a circle of radius 10 about the origin.
+    fn regular(n: usize) -> Vec<Pos2> {
+        (0..n)
+            .map(|i| (10.0 * Vec2::angled(i as f32 * 2.0 * PI / n as f32)).to_pos2())
+            .collect()
+    }
+
+    #[test]
+    fn hull_leaves_out_points_along_its_edges() {
+        let points = [
+            pos2(0.0, 0.0),
+            pos2(1.0, 0.0),
+            pos2(2.0, 0.0),
+            pos2(2.0, 1.0),
+            pos2(2.0, 2.0),
+            pos2(1.0, 2.0),
+            pos2(0.0, 2.0),
+            pos2(0.0, 1.0),
+            pos2(1.0, 1.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            [
+                pos2(0.0, 0.0),
+                pos2(2.0, 0.0),
+                pos2(2.0, 2.0),
+                pos2(0.0, 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn hull_counts_repeated_points_once() {
+        let points = [
+            pos2(3.0, 1.0),
+            pos2(0.0, 0.0),
+            pos2(3.0, 1.0),
+            pos2(1.0, 4.0),
+            pos2(0.0, 0.0),
+            pos2(1.0, 4.0),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(hull, [pos2(0.0, 0.0), pos2(3.0, 1.0), pos2(1.0, 4.0)]);
+        assert!(signed_area(&hull) > 0.0, "{hull:?}");
+    }
+
+    #[test]
+    fn hull_of_points_in_a_line_is_its_ends() {
+        let points = [
+            pos2(2.0, 2.0),
+            pos2(0.0, 0.0),
+            pos2(1.0, 1.0),
+            pos2(1.0, 1.0),
+        ];
+        assert_eq!(convex_hull(&points), [pos2(0.0, 0.0), pos2(2.0, 2.0)]);
+        assert_eq!(
+            convex_hull(&[pos2(1.0, 1.0), pos2(1.0, 1.0)]),
+            [pos2(1.0, 1.0)]
+        );
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[test]
+    fn regular_polygons_have_full_dihedral_symmetry() {
+        for n in 3..=8 {
+            let symmetry = symmetry(&regular(n));
+            assert_eq!(symmetry.order, n, "{symmetry:?}");
+            assert_eq!(symmetry.mirror_axes.len(), n, "{symmetry:?}");
+            assert_eq!(symmetry.group_name(), format!("D{n}"));
+        }
+    }
+
+    #[test]
+    fn finds_the_mirror_lines_of_a_rectangle() {
+        let rectangle = [
+            pos2(-2.0, -1.0),
+            pos2(2.0, -1.0),
+            pos2(2.0, 1.0),
+            pos2(-2.0, 1.0),
+        ];
+        let symmetry = symmetry(&rectangle);
+        assert_eq!(symmetry.order, 2);
+        let mut axes = symmetry.mirror_axes.clone();
+        axes.sort_by(f32::total_cmp);
+        assert_eq!(axes.len(), 2, "{axes:?}");
+        assert!(
+            axes.first().is_some_and(|axis| axis.abs() < 1e-6),
+            "{axes:?}"
+        );
+        assert!(
+            axes.last()
+                .is_some_and(|axis| (axis - FRAC_PI_2).abs() < 1e-6),
+            "{axes:?}"
+        );
+    }
+
+    #[test]
+    fn tells_turns_from_reflections() {
+        let parallelogram = [
+            pos2(-3.0, -1.0),
+            pos2(1.0, -1.0),
+            pos2(3.0, 1.0),
+            pos2(-1.0, 1.0),
+        ];
+        assert_eq!(symmetry(&parallelogram).group_name(), "C2");
+
+        let scalene = [pos2(0.0, 0.0), pos2(6.0, 0.0), pos2(1.0, 3.0)];
+        assert_eq!(
+            symmetry(&scalene),
+            Symmetry {
+                order: 1,
+                mirror_axes: Vec::new()
+            }
+        );
+    }
+
+    #[test]
+    fn allows_for_vertices_a_little_out() {
+        let mut square = regular(4);
+        if let Some(vertex) = square.first_mut() {
+            *vertex += Vec2::new(1e-3, -1e-3);
+        }
+        assert_eq!(symmetry(&square).group_name(), "D4");
+    }
+
+    #[test]
+    fn clipping_leaves_the_area_on_the_near_side() {
+        let square = [
+            pos2(0.0, 0.0),
+            pos2(2.0, 0.0),
+            pos2(2.0, 2.0),
+            pos2(0.0, 2.0),
+        ];
+        let below = |c: f32| clip_to_half_plane(&square, |p: Pos2| p.y - c, |a, b, t| a.lerp(b, t));
+        let area_below = |c: f32| signed_area(&below(c));
+        assert!((area_below(0.5) - 1.0).abs() < 1e-6);
+        assert!((area_below(1.5) - 3.0).abs() < 1e-6);
+        assert!((area_below(3.0) - 4.0).abs() < 1e-6);
+        assert!(below(-1.0).is_empty());
+
+        // Cutting off a corner along the diagonal leaves half the square
+        let half = clip_to_half_plane(&square, |p: Pos2| p.x + p.y - 2.0, |a, b, t| a.lerp(b, t));
+        assert!((signed_area(&half) - 2.0).abs() < 1e-5);
+    }
+}
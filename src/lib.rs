@@ -3,4 +3,7 @@
 mod app;
 pub use app::RupertApp;
 
+pub mod fit;
+pub mod geometry;
+
 mod widgets;
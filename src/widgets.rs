@@ -1,3 +1,24 @@
+pub mod canvas_view;
+pub mod comparison_panel;
+pub mod experiment_panel;
+pub mod explorer_panel;
+pub mod feasibility_map_panel;
 pub mod fit_calculator;
+pub mod growth_panel;
 pub mod poly_fit_display;
 pub mod polygon;
+pub mod sensitivity_panel;
+pub mod sweep_panel;
+
+use egui::DragValue;
+
+/// A drag value for an angle that's stored in radians, but shown in degrees.
+fn degrees_drag_value(ui: &mut egui::Ui, radians: &mut f64) {
+    let mut degrees = radians.to_degrees();
+    if ui
+        .add(DragValue::new(&mut degrees).speed(0.5).suffix("°"))
+        .changed()
+    {
+        *radians = degrees.to_radians();
+    }
+}
@@ -0,0 +1,214 @@
+use egui::{
+    Align2, FontId, Painter, Pos2, Rect, Response, Sense, Stroke, Vec2,
+    emath::{self, RectTransform},
+    pos2,
+};
+
+/// The part of the world that a canvas is looking at, and how big that canvas is on screen.
+///
+/// Scrolling over the canvas zooms about the pointer, dragging its background pans, and
+/// dragging the grip in its bottom-right corner resizes it.
+pub struct CanvasView {
+    /// The world position shown at the centre of the canvas.
+    center: Pos2,
+    /// Screen points per world unit.
+    zoom: f32,
+    /// The size of the canvas on screen.
+    size: Vec2,
+    pub show_grid: bool,
+}
+
+impl Default for CanvasView {
+    fn default() -> Self {
+        Self::new(Pos2::ZERO)
+    }
+}
+
+impl CanvasView {
+    const MIN_SIZE: f32 = 100.0;
+    const MAX_SIZE: f32 = 800.0;
+    const MIN_ZOOM: f32 = 0.01;
+    const MAX_ZOOM: f32 = 100.0;
+    const GRIP_SIZE: f32 = 12.0;
+    /// The closest that grid lines are allowed to get to each other on screen.
+    const MIN_GRID_SPACING: f32 = 30.0;
+
+    pub fn new(center: Pos2) -> Self {
+        Self {
+            center,
+            zoom: 1.0,
+            size: Vec2::splat(200.0),
+            show_grid: true,
+        }
+    }
+
+    /// The distance in world units between neighbouring grid lines at the current zoom: the
+    /// smallest of 1, 2 or 5 times a power of ten that keeps the lines legibly far apart.
+    pub fn grid_spacing(&self) -> f32 {
+        let min_spacing = Self::MIN_GRID_SPACING / self.zoom;
+        let power = 10.0f32.powf(min_spacing.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|multiplier| multiplier * power)
+            .find(|&spacing| spacing >= min_spacing)
+            .unwrap_or(10.0 * power)
+    }
+
+    /// The transform from world coordinates to screen coordinates, for a canvas occupying
+    /// `screen_rect`.
+    pub fn to_screen(&self, screen_rect: Rect) -> RectTransform {
+        emath::RectTransform::from_to(
+            Rect::from_center_size(self.center, screen_rect.size() / self.zoom),
+            screen_rect,
+        )
+    }
+
+    /// Allocates the canvas and applies any zooming, panning or resizing the user has done to
+    /// it this frame.
+    ///
+    /// Widgets drawn on the canvas afterwards sit on top of it, so dragging them doesn't also
+    /// pan the view.
+    pub fn allocate_painter(&mut self, ui: &mut egui::Ui) -> (Response, egui::Painter) {
+        let (response, painter) = ui.allocate_painter(self.size, Sense::click_and_drag());
+        if self.show_grid {
+            self.paint_grid(ui, &painter, response.rect);
+        }
+
+        if response.dragged() {
+            self.center -= response.drag_delta() / self.zoom;
+        }
+
+        if let Some(hover_pos) = response.hover_pos() {
+            let (scroll, pinch) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = (scroll / 200.0).exp() * pinch;
+            if factor != 1.0 {
+                self.zoom_about(response.rect, hover_pos, factor);
+                // Scrolling over the canvas zooms it, rather than also scrolling the page
+                ui.input_mut(|i| i.smooth_scroll_delta = Vec2::ZERO);
+            }
+        }
+
+        let grip_rect = Rect::from_min_max(
+            response.rect.max - Vec2::splat(Self::GRIP_SIZE),
+            response.rect.max,
+        );
+        let grip_response = ui.interact(grip_rect, response.id.with("resize"), Sense::drag());
+        if grip_response.dragged() {
+            self.size = (self.size + grip_response.drag_delta())
+                .clamp(Vec2::splat(Self::MIN_SIZE), Vec2::splat(Self::MAX_SIZE));
+        }
+        let grip_stroke = Stroke::new(1.0, ui.style().interact(&grip_response).fg_stroke.color);
+        for offset in [4.0, 8.0] {
+            painter.line_segment(
+                [
+                    pos2(grip_rect.max.x - offset, grip_rect.max.y - 1.0),
+                    pos2(grip_rect.max.x - 1.0, grip_rect.max.y - offset),
+                ],
+                grip_stroke,
+            );
+        }
+
+        (response, painter)
+    }
+
+    /// Draws grid lines at every multiple of [`Self::grid_spacing`], with the axes picked out
+    /// and each line labelled with its coordinate along the top and left edges.
+    fn paint_grid(&self, ui: &egui::Ui, painter: &Painter, screen_rect: Rect) {
+        let to_screen = self.to_screen(screen_rect);
+        let world_rect = *to_screen.from();
+        let spacing = self.grid_spacing();
+        let decimals = (-spacing.log10()).ceil().max(0.0) as usize;
+
+        let visuals = &ui.style().visuals;
+        let line_stroke = Stroke::new(0.5, visuals.weak_text_color().gamma_multiply(0.3));
+        let axis_stroke = Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.6));
+        let font_id = FontId::monospace(9.0);
+        let label_color = visuals.weak_text_color();
+
+        let first_x = (world_rect.min.x / spacing).ceil() as i64;
+        let last_x = (world_rect.max.x / spacing).floor() as i64;
+        for k in first_x..=last_x {
+            let x = k as f32 * spacing;
+            let screen_x = to_screen.transform_pos(pos2(x, 0.0)).x;
+            let stroke = if k == 0 { axis_stroke } else { line_stroke };
+            painter.vline(screen_x, screen_rect.y_range(), stroke);
+            painter.text(
+                pos2(screen_x + 2.0, screen_rect.min.y),
+                Align2::LEFT_TOP,
+                format!("{x:.decimals$}"),
+                font_id.clone(),
+                label_color,
+            );
+        }
+
+        let first_y = (world_rect.min.y / spacing).ceil() as i64;
+        let last_y = (world_rect.max.y / spacing).floor() as i64;
+        for k in first_y..=last_y {
+            let y = k as f32 * spacing;
+            let screen_y = to_screen.transform_pos(pos2(0.0, y)).y;
+            let stroke = if k == 0 { axis_stroke } else { line_stroke };
+            painter.hline(screen_rect.x_range(), screen_y, stroke);
+            painter.text(
+                pos2(screen_rect.min.x + 2.0, screen_y),
+                Align2::LEFT_BOTTOM,
+                format!("{y:.decimals$}"),
+                font_id.clone(),
+                label_color,
+            );
+        }
+    }
+
+    /// Labels the bottom-left corner of the canvas with the world position under the pointer,
+    /// if it's over the canvas. Call this after drawing everything else, so it stays on top.
+    pub fn paint_cursor_readout(&self, ui: &egui::Ui, painter: &Painter, screen_rect: Rect) {
+        let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+        if !screen_rect.contains(pointer) {
+            return;
+        }
+
+        let world_pos = self.to_screen(screen_rect).inverse() * pointer;
+        painter.text(
+            screen_rect.left_bottom() + Vec2::new(4.0, -4.0),
+            Align2::LEFT_BOTTOM,
+            format!("({:.2}, {:.2})", world_pos.x, world_pos.y),
+            FontId::monospace(10.0),
+            ui.style().visuals.text_color(),
+        );
+    }
+
+    /// Scales the view by `factor`, keeping the world position under `screen_pos` fixed.
+    fn zoom_about(&mut self, screen_rect: Rect, screen_pos: Pos2, factor: f32) {
+        let world_pos = self.to_screen(screen_rect).inverse() * screen_pos;
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.center = world_pos - (screen_pos - screen_rect.center()) / self.zoom;
+    }
+
+    /// Centres the view on `points` and zooms so that they fill most of the canvas.
+    pub fn fit_to_content(&mut self, points: &[Pos2]) {
+        let bounds = Rect::from_points(points);
+        if !bounds.is_finite() {
+            return;
+        }
+
+        self.center = bounds.center();
+        let size = bounds.size().max(Vec2::splat(f32::EPSILON));
+        self.zoom = (0.9 * (self.size / size).min_elem()).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+    }
+
+    /// A row of buttons for resetting the view, fitting it to `content`, and reading off the
+    /// current zoom level.
+    pub fn ui_controls(&mut self, ui: &mut egui::Ui, content: &[Pos2]) {
+        ui.horizontal(|ui| {
+            if ui.small_button("Fit").clicked() {
+                self.fit_to_content(content);
+            }
+            if ui.small_button("1:1").clicked() {
+                self.zoom = 1.0;
+            }
+            ui.checkbox(&mut self.show_grid, "Grid");
+            ui.weak(format!("{:.0}%", self.zoom * 100.0));
+        });
+    }
+}
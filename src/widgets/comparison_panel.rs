@@ -0,0 +1,121 @@
+use egui::{Grid, RichText};
+
+use crate::{
+    fit::{Fit, FitData, SolverKind, solve},
+    widgets::fit_calculator::SolveInputs,
+};
+
+/// Runs every solver that can handle A on the same polygons, to check that they agree with
+/// the selected one.
+#[derive(Default)]
+pub struct ComparisonPanel {
+    enabled: bool,
+    /// What each solver made of the polygons, in the order of [`SolverKind::ALL`]. Until
+    /// they've all been run again, some may be for the polygons as they were.
+    comparison: Vec<(SolverKind, FitData)>,
+    /// The solvers still to be run on [`Self::inputs`], one a frame, as some of them are slow.
+    pending: Vec<SolverKind>,
+    /// What the solvers were last started on, so that they're only run again when something
+    /// changes.
+    inputs: Option<SolveInputs>,
+}
+
+impl ComparisonPanel {
+    /// Starts running every solver again if they're being compared and anything has changed,
+    /// and runs the next one.
+    pub fn update(&mut self, ctx: &egui::Context, inputs: &SolveInputs) {
+        if !self.enabled {
+            self.comparison.clear();
+            self.pending.clear();
+            self.inputs = None;
+            return;
+        }
+
+        if self.inputs.as_ref() != Some(inputs) {
+            let supported = |kind: &SolverKind| kind.supports(inputs.outer_vertices.len());
+            self.comparison.retain(|(kind, _)| supported(kind));
+            self.pending = SolverKind::ALL.into_iter().filter(supported).collect();
+            self.inputs = Some(inputs.clone());
+        }
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let kind = self.pending.remove(0);
+        let data = solve(
+            kind.solver(),
+            &inputs.outer_vertices,
+            &inputs.inner_vertices,
+            &inputs.settings,
+        );
+        self.comparison.retain(|(other, _)| *other != kind);
+        self.comparison.push((kind, data));
+        self.comparison
+            .sort_by_key(|(kind, _)| SolverKind::ALL.iter().position(|other| other == kind));
+        ctx.request_repaint();
+    }
+
+    /// A table of what every solver makes of the polygons, with any disagreement with
+    /// `selected`, the selected solver's answer, picked out. Margins within `tolerance` of
+    /// one another count as agreeing.
+    pub fn ui_content(&mut self, ui: &mut egui::Ui, selected: &FitData, tolerance: f64) {
+        ui.checkbox(&mut self.enabled, "Compare solvers");
+        if let Some(kind) = self.pending.first() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak(format!("Running the {} solver", kind.name().to_lowercase()));
+            });
+        }
+        if self.comparison.is_empty() {
+            return;
+        }
+
+        let selected_fits = matches!(selected.fit, Fit::Fit { .. });
+        let selected_margin = selected.margin;
+        let warn_color = ui.visuals().warn_fg_color;
+        let mut any_disagreement = false;
+
+        Grid::new("solver_comparison").striped(true).show(ui, |ui| {
+            ui.label("Solver");
+            ui.label("Verdict");
+            ui.label("Margin");
+            ui.label("Angle");
+            ui.end_row();
+
+            for (kind, data) in &self.comparison {
+                let fits = matches!(data.fit, Fit::Fit { .. });
+                let margin_differs = match (data.margin, selected_margin) {
+                    (Some(margin), Some(selected)) => (margin - selected).abs() > tolerance,
+                    _ => false,
+                };
+                any_disagreement |= fits != selected_fits || margin_differs;
+
+                let text = |text: String, differs: bool| {
+                    if differs {
+                        RichText::new(text).color(warn_color)
+                    } else {
+                        RichText::new(text)
+                    }
+                };
+                ui.label(kind.name());
+                ui.label(text(
+                    if fits { "Fit" } else { "No fit" }.to_owned(),
+                    fits != selected_fits,
+                ));
+                ui.label(text(
+                    data.margin.map_or("–".to_owned(), |m| format!("{m:.3}")),
+                    margin_differs,
+                ));
+                ui.label(match data.fit {
+                    Fit::Fit { angle, .. } => format!("{:.1}°", angle.to_degrees()),
+                    Fit::NoFit | Fit::Unsupported => "–".to_owned(),
+                });
+                ui.end_row();
+            }
+        });
+
+        if any_disagreement {
+            ui.colored_label(warn_color, "The solvers disagree");
+        }
+    }
+}
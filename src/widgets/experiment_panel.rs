@@ -0,0 +1,253 @@
+use egui::{Color32, ComboBox, DragValue, Pos2, ProgressBar, ScrollArea};
+use egui_plot::{Bar, BarChart, Plot};
+
+use crate::fit::{
+    PolygonDistribution, PolygonFamily, RandomExperiment, SolverKind, SolverSettings, TrialPair,
+};
+
+/// How many pairs are drawn and solved each frame while an experiment is running.
+const TRIALS_PER_FRAME: usize = 16;
+
+/// How many bars the histogram of the margin is split into.
+const NUM_BINS: usize = 30;
+
+/// Draws random pairs of polygons, to estimate how likely it is that B fits in A, and shows
+/// how the margin is spread.
+pub struct ExperimentPanel {
+    outer: PolygonDistribution,
+    inner: PolygonDistribution,
+    num_trials: usize,
+    seed: u64,
+    experiment: Option<RandomExperiment>,
+}
+
+impl Default for ExperimentPanel {
+    fn default() -> Self {
+        Self {
+            outer: PolygonDistribution::new(PolygonFamily::OnCircle, 3, 60.0),
+            inner: PolygonDistribution::new(PolygonFamily::RandomHull, 5, 20.0),
+            num_trials: 500,
+            seed: 1,
+            experiment: None,
+        }
+    }
+}
+
+impl ExperimentPanel {
+    /// The controls, the results so far, and a list of the pairs drawn.
+    ///
+    /// Returns the pair to open in the editor, centred on their centroids, if one has just
+    /// been picked.
+    pub fn ui_content(
+        &mut self,
+        ui: &mut egui::Ui,
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Option<(Vec<Pos2>, Vec<Pos2>)> {
+        let triangles_only = solver == SolverKind::CriticalRegion;
+        ui.label("A:");
+        ui_distribution(ui, "experiment_outer", &mut self.outer, triangles_only);
+        ui.label("B:");
+        ui_distribution(ui, "experiment_inner", &mut self.inner, false);
+
+        ui.horizontal(|ui| {
+            ui.label("Pairs");
+            ui.add(DragValue::new(&mut self.num_trials).range(1..=100_000));
+            ui.label("Seed");
+            ui.add(DragValue::new(&mut self.seed));
+            if ui
+                .button("New seed")
+                .on_hover_text("Pick a seed at random")
+                .clicked()
+            {
+                self.seed = fastrand::u64(..);
+            }
+        });
+
+        let running = self
+            .experiment
+            .as_ref()
+            .is_some_and(|experiment| !experiment.is_finished());
+        ui.horizontal(|ui| {
+            if running {
+                if ui.button("Stop").clicked() {
+                    self.experiment = None;
+                }
+            } else if ui
+                .button("Run")
+                .on_hover_text("The same seed always draws the same pairs")
+                .clicked()
+            {
+                self.experiment = Some(RandomExperiment::new(
+                    self.outer,
+                    self.inner,
+                    self.num_trials,
+                    self.seed,
+                    solver,
+                    settings,
+                ));
+            }
+        });
+
+        let experiment = self.experiment.as_mut()?;
+        if !experiment.is_finished() {
+            experiment.run_batch(TRIALS_PER_FRAME);
+            ui.add(ProgressBar::new(experiment.progress()).show_percentage());
+            ui.ctx().request_repaint();
+        }
+        if let Some((fraction, error)) = experiment.fit_fraction() {
+            let trials = experiment.trials();
+            let num_fits = trials.iter().filter(|trial| trial.fits).count();
+            ui.label(format!(
+                "B fits in {num_fits} of {} pairs: {:.1}% ± {:.1}%",
+                trials.len(),
+                100.0 * fraction,
+                // A 95% confidence interval
+                100.0 * 1.96 * error,
+            ));
+        }
+        ui_histogram(ui, experiment.trials());
+        ui_trials(ui, experiment.trials(), solver)
+    }
+}
+
+/// The controls for how one of the polygons is drawn. With `triangles_only`, it's held to
+/// three vertices.
+pub fn ui_distribution(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    distribution: &mut PolygonDistribution,
+    triangles_only: bool,
+) {
+    ui.horizontal(|ui| {
+        ComboBox::from_id_salt(id_salt)
+            .selected_text(distribution.family.name())
+            .show_ui(ui, |ui| {
+                for family in PolygonFamily::ALL {
+                    ui.selectable_value(&mut distribution.family, family, family.name())
+                        .on_hover_text(family.description());
+                }
+            });
+        if triangles_only {
+            distribution.num_vertices = 3;
+            ui.label("with 3")
+                .on_hover_text("The critical-region solver only handles triangular A");
+        } else {
+            ui.label("with");
+            ui.add(DragValue::new(&mut distribution.num_vertices).range(3..=20));
+        }
+        ui.label(match distribution.family {
+            PolygonFamily::RandomHull => "points, radius",
+            PolygonFamily::OnCircle | PolygonFamily::PerturbedRegular => "vertices, radius",
+        });
+        ui.add(
+            DragValue::new(&mut distribution.radius)
+                .range(1.0..=1000.0)
+                .speed(0.5),
+        );
+        if distribution.family == PolygonFamily::PerturbedRegular {
+            ui.label("jitter");
+            ui.add(
+                DragValue::new(&mut distribution.jitter)
+                    .range(0.0..=1.0)
+                    .speed(0.01),
+            )
+            .on_hover_text("How far each vertex can move, as a fraction of the radius");
+        }
+    });
+}
+
+/// What opening a pair with A as `outer_vertices` does, which includes switching solvers if
+/// `solver` can't handle A.
+pub fn open_hover_text(solver: SolverKind, outer_vertices: &[Pos2]) -> &'static str {
+    if solver.supports(outer_vertices.len()) {
+        "Load this pair into the editor"
+    } else {
+        "Load this pair into the editor, switching to a solver that can handle A"
+    }
+}
+
+/// How the margin is spread over the pairs, split by whether B fits.
+fn ui_histogram(ui: &mut egui::Ui, trials: &[TrialPair]) {
+    let margins = || trials.iter().filter_map(|trial| trial.margin);
+    let (min, max) = margins().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), margin| {
+        (min.min(margin), max.max(margin))
+    });
+    if min > max {
+        return;
+    }
+    let width = if max > min {
+        (max - min) / NUM_BINS as f64
+    } else {
+        1.0
+    };
+    let bars = |fits: bool| {
+        let mut counts = vec![0_usize; NUM_BINS];
+        for trial in trials.iter().filter(|trial| trial.fits == fits) {
+            if let Some(margin) = trial.margin {
+                let bin = (((margin - min) / width) as usize).min(NUM_BINS - 1);
+                if let Some(count) = counts.get_mut(bin) {
+                    *count += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .map(|(bin, count)| {
+                Bar::new(min + (bin as f64 + 0.5) * width, count as f64).width(width)
+            })
+            .collect()
+    };
+    let fits = BarChart::new("Fits", bars(true)).color(Color32::from_rgb(100, 200, 100));
+    let no_fit = BarChart::new("No fit", bars(false)).color(Color32::from_rgb(200, 100, 100));
+
+    Plot::new("experiment_histogram")
+        .width(600.0)
+        .height(200.0)
+        .x_axis_label("Margin")
+        .y_axis_label("Pairs")
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(fits);
+            plot_ui.bar_chart(no_fit);
+        });
+}
+
+/// Every pair drawn, each with a button to open it in the editor. Returns the pair whose
+/// button was clicked, if any.
+fn ui_trials(
+    ui: &mut egui::Ui,
+    trials: &[TrialPair],
+    solver: SolverKind,
+) -> Option<(Vec<Pos2>, Vec<Pos2>)> {
+    let mut opened = None;
+    let row_height = ui.spacing().interact_size.y;
+    ScrollArea::vertical()
+        .max_height(200.0)
+        .show_rows(ui, row_height, trials.len(), |ui, rows| {
+            for (i, trial) in trials.iter().enumerate().skip(rows.start).take(rows.len()) {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}.", i + 1));
+                    ui.label(
+                        trial
+                            .margin
+                            .map_or("—".to_owned(), |margin| format!("{margin:.3}")),
+                    );
+                    if trial.fits {
+                        ui.label("fits");
+                    } else {
+                        ui.colored_label(ui.visuals().error_fg_color, "doesn't fit");
+                    }
+                    if ui
+                        .button("Open")
+                        .on_hover_text(open_hover_text(solver, &trial.outer_vertices))
+                        .clicked()
+                    {
+                        opened = Some((trial.outer_vertices.clone(), trial.inner_vertices.clone()));
+                    }
+                });
+            }
+        });
+    opened
+}
@@ -0,0 +1,182 @@
+use egui::{ComboBox, DragValue, Grid};
+
+use crate::{
+    fit::{
+        Explorer, Find, PolygonDistribution, PolygonFamily, SearchCriterion, SearchStrategy,
+        SolverKind, SolverSettings,
+    },
+    widgets::experiment_panel::{open_hover_text, ui_distribution},
+};
+
+/// How many pairs the explorer tries each frame while it's running.
+const PAIRS_PER_FRAME: usize = 8;
+
+/// What to do with one of the explorer's finds.
+pub enum FindAction {
+    /// Load it into the editor.
+    Open,
+    /// Keep it as a scenario under `name`, to be solved with `solver`.
+    Save { name: String, solver: SolverKind },
+}
+
+/// Searches for pairs of polygons where B fits and some measure of how surprising that is
+/// comes out as high as possible, and lists the best finds.
+pub struct ExplorerPanel {
+    criterion: SearchCriterion,
+    strategy: SearchStrategy,
+    outer: PolygonDistribution,
+    inner: PolygonDistribution,
+    seed: u64,
+    explorer: Option<Explorer>,
+    running: bool,
+}
+
+impl Default for ExplorerPanel {
+    fn default() -> Self {
+        Self {
+            criterion: SearchCriterion::default(),
+            strategy: SearchStrategy::default(),
+            outer: PolygonDistribution::new(PolygonFamily::OnCircle, 3, 60.0),
+            inner: PolygonDistribution::new(PolygonFamily::RandomHull, 5, 20.0),
+            seed: 1,
+            explorer: None,
+            running: false,
+        }
+    }
+}
+
+impl ExplorerPanel {
+    /// The controls and the list of the best finds so far.
+    ///
+    /// Returns one of the finds, and what to do with it, if one of its buttons has just been
+    /// clicked.
+    pub fn ui_content(
+        &mut self,
+        ui: &mut egui::Ui,
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Option<(FindAction, Find)> {
+        ui.horizontal(|ui| {
+            ComboBox::from_label("Maximise")
+                .selected_text(self.criterion.name())
+                .show_ui(ui, |ui| {
+                    for criterion in SearchCriterion::ALL {
+                        ui.selectable_value(&mut self.criterion, criterion, criterion.name())
+                            .on_hover_text(criterion.description());
+                    }
+                });
+            ComboBox::from_label("by")
+                .selected_text(self.strategy.name())
+                .show_ui(ui, |ui| {
+                    for strategy in SearchStrategy::ALL {
+                        ui.selectable_value(&mut self.strategy, strategy, strategy.name())
+                            .on_hover_text(strategy.description());
+                    }
+                });
+        });
+        let triangles_only = solver == SolverKind::CriticalRegion;
+        ui.label("Drawing A from:");
+        ui_distribution(ui, "explorer_outer", &mut self.outer, triangles_only);
+        ui.label("and B from:");
+        ui_distribution(ui, "explorer_inner", &mut self.inner, false);
+        ui.horizontal(|ui| {
+            ui.label("Seed");
+            ui.add(DragValue::new(&mut self.seed));
+            if ui
+                .button("New seed")
+                .on_hover_text("Pick a seed at random")
+                .clicked()
+            {
+                self.seed = fastrand::u64(..);
+            }
+        });
+
+        self.ui_controls(ui, solver, settings);
+
+        let explorer = self.explorer.as_mut()?;
+        if self.running {
+            for _ in 0..PAIRS_PER_FRAME {
+                explorer.run_next();
+            }
+            ui.ctx().request_repaint();
+        }
+        ui.label(format!("{} pairs tried.", explorer.num_evaluations()));
+        if let Some(score) = explorer.climb_score() {
+            ui.label(format!("The current climb has got to {score:.4}."));
+        }
+        ui_finds(ui, explorer, solver)
+    }
+
+    /// Starting afresh, and pausing and resuming.
+    fn ui_controls(&mut self, ui: &mut egui::Ui, solver: SolverKind, settings: &SolverSettings) {
+        ui.horizontal(|ui| {
+            if ui
+                .button("Start")
+                .on_hover_text("Start a new search, forgetting the finds so far")
+                .clicked()
+            {
+                self.explorer = Some(Explorer::new(
+                    self.criterion,
+                    self.strategy,
+                    self.outer,
+                    self.inner,
+                    self.seed,
+                    solver,
+                    settings,
+                ));
+                self.running = true;
+            }
+            if self.explorer.is_some() {
+                let label = if self.running { "Pause" } else { "Resume" };
+                if ui.button(label).clicked() {
+                    self.running = !self.running;
+                }
+            }
+        });
+    }
+}
+
+/// The best finds, best first, each with buttons to open or save it. Returns the find whose
+/// button was clicked, if any. Opening a find switches away from `solver` if it can't handle
+/// the find's A.
+fn ui_finds(
+    ui: &mut egui::Ui,
+    explorer: &Explorer,
+    solver: SolverKind,
+) -> Option<(FindAction, Find)> {
+    let mut picked = None;
+    let criterion = explorer.criterion();
+    Grid::new("explorer_finds").striped(true).show(ui, |ui| {
+        ui.label("");
+        ui.label(criterion.name());
+        ui.label("Margin");
+        ui.end_row();
+
+        for (rank, find) in explorer.finds().iter().enumerate() {
+            ui.label(format!("{}.", rank + 1));
+            ui.label(format!("{:.4}", find.score));
+            ui.label(
+                find.margin
+                    .map_or("—".to_owned(), |margin| format!("{margin:.3}")),
+            );
+            if ui
+                .button("Open")
+                .on_hover_text(open_hover_text(solver, &find.outer_vertices))
+                .clicked()
+            {
+                picked = Some((FindAction::Open, find.clone()));
+            }
+            if ui
+                .button("Save")
+                .on_hover_text("Keep this pair as a saved scenario")
+                .clicked()
+            {
+                let name = format!("{} {:.4}", criterion.name(), find.score);
+                let solver = explorer.solver();
+                picked = Some((FindAction::Save { name, solver }, find.clone()));
+            }
+            ui.end_row();
+        }
+    });
+    picked
+}
@@ -0,0 +1,211 @@
+use egui::{Color32, DragValue, Pos2, ProgressBar, Stroke};
+use egui_plot::{Line, Plot, PlotPoints, Polygon};
+
+use crate::{
+    fit::{
+        FeasibilityMap, MapAxis, SolverKind, SolverSettings, SweepOutcome, SweepParameter, Which,
+    },
+    widgets::sweep_panel::{ParameterPicker, outcome_colour},
+};
+
+/// Sweeps two shape parameters over a grid, solving in every cell, and shows the margin as a
+/// heatmap with the boundary between fitting and not drawn over it.
+pub struct FeasibilityMapPanel {
+    x_picker: ParameterPicker,
+    y_picker: ParameterPicker,
+    /// How many cells along each side of the grid.
+    resolution: usize,
+    map: Option<FeasibilityMap>,
+}
+
+impl Default for FeasibilityMapPanel {
+    fn default() -> Self {
+        Self {
+            x_picker: ParameterPicker::new(SweepParameter::Scale(Which::Inner), (0.5, 2.0)),
+            y_picker: ParameterPicker::new(SweepParameter::AspectRatio(Which::Inner), (0.5, 2.0)),
+            resolution: 20,
+            map: None,
+        }
+    }
+}
+
+impl FeasibilityMapPanel {
+    /// The controls and the map. Both sets of vertices should be centred on their
+    /// centroids.
+    ///
+    /// Returns both polygons, centred on their centroids, as they are in a cell of the map
+    /// that's just been clicked on.
+    pub fn ui_content(
+        &mut self,
+        ui: &mut egui::Ui,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) -> Option<(Vec<Pos2>, Vec<Pos2>)> {
+        ui.label("Across:");
+        self.x_picker
+            .ui(ui, "map_x", outer_vertices, inner_vertices);
+        ui.label("Up:");
+        self.y_picker
+            .ui(ui, "map_y", outer_vertices, inner_vertices);
+        ui.horizontal(|ui| {
+            ui.label("Resolution");
+            ui.add(DragValue::new(&mut self.resolution).range(2..=100));
+            ui.label(format!("× {}", self.resolution));
+        });
+
+        let running = self.map.as_ref().is_some_and(|map| !map.is_finished());
+        ui.horizontal(|ui| {
+            if running {
+                if ui.button("Stop").clicked() {
+                    self.map = None;
+                }
+            } else if ui.button("Run").clicked() {
+                self.map = Some(FeasibilityMap::new(
+                    MapAxis::new(
+                        self.x_picker.parameter,
+                        self.x_picker.range,
+                        self.resolution,
+                    ),
+                    MapAxis::new(
+                        self.y_picker.parameter,
+                        self.y_picker.range,
+                        self.resolution,
+                    ),
+                    outer_vertices,
+                    inner_vertices,
+                    solver,
+                    settings,
+                ));
+            }
+        });
+
+        let map = self.map.as_mut()?;
+        if !map.is_finished() {
+            map.run_next_row();
+            ui.add(ProgressBar::new(map.progress()).show_percentage());
+            ui.ctx().request_repaint();
+        }
+        ui.label("Click on a cell to load the polygons as they are there.");
+        ui.weak("Grey cells leave a polygon that isn't convex, and amber ones an A the solver can't handle.");
+        let (i, j) = Self::ui_heatmap(ui, map)?;
+        map.configuration(i, j)
+    }
+
+    /// The margin in each cell, green where B fits and red where it doesn't, getting
+    /// stronger the further the margin is from zero, and faintly in the [`outcome_colour`]
+    /// where nothing was solved. Returns the cell clicked on, if any.
+    fn ui_heatmap(ui: &mut egui::Ui, map: &FeasibilityMap) -> Option<(usize, usize)> {
+        let (x, y) = (map.x(), map.y());
+        let largest = (0..x.resolution)
+            .flat_map(|i| (0..y.resolution).map(move |j| (i, j)))
+            .filter_map(|(i, j)| map.cell(i, j)?.margin)
+            .fold(0.0_f64, |max, margin| max.max(margin.abs()));
+
+        let cells: Vec<Polygon<'_>> = (0..y.resolution)
+            .flat_map(|j| (0..x.resolution).map(move |i| (i, j)))
+            .filter_map(|(i, j)| {
+                let cell = map.cell(i, j)?;
+                let colour = match (cell.outcome, cell.margin) {
+                    (SweepOutcome::Fit | SweepOutcome::NoFit, Some(margin)) => {
+                        let strength = if largest > 0.0 {
+                            0.2 + 0.8 * (margin.abs() / largest) as f32
+                        } else {
+                            1.0
+                        };
+                        outcome_colour(cell.outcome).linear_multiply(strength)
+                    }
+                    (outcome, _) => outcome_colour(outcome).linear_multiply(0.25),
+                };
+                let (x0, y0) = (
+                    x.value(i) - x.cell_size() / 2.0,
+                    y.value(j) - y.cell_size() / 2.0,
+                );
+                let (x1, y1) = (x0 + x.cell_size(), y0 + y.cell_size());
+                Some(
+                    Polygon::new(
+                        "Margin",
+                        PlotPoints::new(vec![[x0, y0], [x1, y0], [x1, y1], [x0, y1]]),
+                    )
+                    .fill_color(colour)
+                    .stroke(Stroke::NONE),
+                )
+            })
+            .collect();
+        let boundary: Vec<Line<'_>> = fit_boundary(map)
+            .into_iter()
+            .map(|[from, to]| {
+                Line::new("Fit boundary", PlotPoints::new(vec![from, to]))
+                    .color(Color32::WHITE)
+                    .width(2.0)
+            })
+            .collect();
+
+        Plot::new("feasibility_map")
+            .width(600.0)
+            .height(400.0)
+            .x_axis_label(x.parameter.name())
+            .y_axis_label(y.parameter.name())
+            .allow_boxed_zoom(false)
+            .show(ui, |plot_ui| {
+                for cell in cells {
+                    plot_ui.polygon(cell);
+                }
+                for line in boundary {
+                    plot_ui.line(line);
+                }
+                if plot_ui.response().clicked() {
+                    let point = plot_ui.pointer_coordinate()?;
+                    map.cell_at(point.x, point.y)
+                } else {
+                    None
+                }
+            })
+            .inner
+    }
+}
+
+/// The line where the margin crosses zero, as segments between the middles of the cells,
+/// found by marching squares. Only the cells that have been solved and have a margin count.
+fn fit_boundary(map: &FeasibilityMap) -> Vec<[[f64; 2]; 2]> {
+    let (x, y) = (map.x(), map.y());
+    let corner = |i: usize, j: usize| {
+        let margin = map.cell(i, j)?.margin?;
+        Some(([x.value(i), y.value(j)], margin))
+    };
+    // Where the margin is zero along the line between two corners, if it is at all
+    let crossing = |(a, margin_a): ([f64; 2], f64), (b, margin_b): ([f64; 2], f64)| {
+        ((margin_a > 0.0) != (margin_b > 0.0)).then(|| {
+            let t = margin_a / (margin_a - margin_b);
+            [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+        })
+    };
+
+    let mut segments = Vec::new();
+    for j in 1..y.resolution {
+        for i in 1..x.resolution {
+            let (Some(c00), Some(c10), Some(c11), Some(c01)) = (
+                corner(i - 1, j - 1),
+                corner(i, j - 1),
+                corner(i, j),
+                corner(i - 1, j),
+            ) else {
+                continue;
+            };
+            // Going round the square, so that where the boundary crosses all four sides the
+            // crossings are paired up with their neighbours
+            let crossings: Vec<[f64; 2]> = [(c00, c10), (c10, c11), (c11, c01), (c01, c00)]
+                .into_iter()
+                .filter_map(|(a, b)| crossing(a, b))
+                .collect();
+            if let [a, b, rest @ ..] = crossings.as_slice() {
+                segments.push([*a, *b]);
+                if let [c, d] = rest {
+                    segments.push([*c, *d]);
+                }
+            }
+        }
+    }
+    segments
+}
@@ -1,221 +1,99 @@
-use std::f32::consts::PI;
+use std::{cmp::Ordering, f64::consts::TAU};
 
-use egui::{Color32, Frame, Grid, Stroke, Vec2};
-use itertools::{Itertools as _, repeat_n};
-use nalgebra::{Matrix2x3, Matrix3x2, Vector2, Vector3, matrix, vector};
+use egui::{
+    CollapsingHeader, Color32, ComboBox, DragValue, Frame, Grid, Pos2, Rect, ScrollArea, Slider,
+    Stroke, Vec2,
+};
 
-use crate::widgets::{poly_fit_display::PolyFitDisplay, polygon::Polygon};
-
-enum Fit {
-    NoFit,
-    Fit {
-        angle: f32,
-        translation: Vector2<f32>,
+use crate::{
+    fit::{
+        ActiveConstraint, AngleInterval, AngleRestriction, FeasibleInterval, Fit, FitData,
+        PlotData, PoseObjective, Restrictions, SolverKind, SolverSettings, TranslationBound,
+        Verdict, solve,
     },
-}
-
-#[derive(Clone)]
-pub struct PlotData {
-    pub xs: Vec<f32>,
-    pub yss: Vec<Vec<f32>>,
-    pub min_ys: Vec<f32>,
-}
-
-struct FitData {
-    fit: Fit,
-    plot_data: PlotData,
-}
+    widgets::{
+        canvas_view::CanvasView,
+        comparison_panel::ComparisonPanel,
+        degrees_drag_value,
+        experiment_panel::ExperimentPanel,
+        explorer_panel::{ExplorerPanel, FindAction},
+        feasibility_map_panel::FeasibilityMapPanel,
+        growth_panel::GrowthPanel,
+        poly_fit_display::{PolyFitDisplay, Pose},
+        polygon::Polygon,
+        sensitivity_panel::SensitivityPanel,
+        sweep_panel::SweepPanel,
+    },
+};
 
 pub struct FitCalculator {
     outer_polygon: Polygon,
     inner_polygon: Polygon,
     fit_data: FitData,
+    /// What [`Self::fit_data`] was worked out for, so that it's only redone when something
+    /// changes.
+    solved_inputs: Option<SolveInputs>,
+    fit_view: CanvasView,
+    solver_settings: SolverSettings,
+    solver: SolverKind,
+    comparison_panel: ComparisonPanel,
+    /// Where B has been put by hand, if it's being placed by hand rather than by the solver.
+    manual_pose: Option<Pose>,
+    sensitivity_panel: SensitivityPanel,
+    growth_panel: GrowthPanel,
+    sweep_panel: SweepPanel,
+    map_panel: FeasibilityMapPanel,
+    experiment_panel: ExperimentPanel,
+    explorer_panel: ExplorerPanel,
+    /// Scenarios kept under a name, such as the explorer's finds.
+    saved_scenarios: Vec<SavedScenario>,
 }
 
-impl FitCalculator {
-    fn calculate_data(outer_polygon: &Polygon, inner_polygon: &Polygon) -> FitData {
-        // TODO: Refactor this, I don't like it
-
-        // Calculate ns and cs
-        let outer_vertices = outer_polygon.centered_vertices();
-        let mut ns: Vec<Vec2> = Vec::new();
-        let mut cs: Vec<f32> = Vec::new();
-        for to_idx in 0..outer_vertices.len() {
-            let mut from_idx = outer_vertices.len() - 1;
-            if to_idx != 0 {
-                from_idx = to_idx - 1;
-            }
-
-            let edge = outer_vertices[to_idx].to_vec2() - outer_vertices[from_idx].to_vec2();
-            // Compute the normal by rotating the edge clockwise 90 degrees and normalising
-            // This works because we have defined the polygon to have its vertices defined
-            // anti-clockwise
-            // TODO: Make this method robust by detecting which way round the vertices are
-            // defined
-            let n = edge.rot90().normalized();
-            let c = n.dot(outer_vertices[to_idx].to_vec2());
-
-            ns.push(n);
-            cs.push(c);
-        }
-
-        // Find the critical region
-        // TODO: Generalise to N_A > 3
-        let n_1 = ns[0];
-        let n_2 = ns[1];
-        let n_3 = ns[2];
-        let n_p: Vector3<f32> = vector![
-            n_2.x * n_3.y - n_3.x * n_2.y,
-            n_3.x * n_1.y - n_1.x * n_3.y,
-            n_1.x * n_2.y - n_2.x * n_1.y,
-        ];
-        assert!(n_p[0] >= 0.0);
-        assert!(n_p[1] >= 0.0);
-        assert!(n_p[2] >= 0.0);
-        let n_mat: Matrix3x2<f32> = matrix![
-            n_1.x, n_1.y;
-            n_2.x, n_2.y;
-            n_3.x, n_3.y;
-        ];
-
-        // Values we need
-        let inner_vertices = inner_polygon.centered_vertices().clone();
-
-        // Generate x and y values
-        let num_points: usize = 400;
-        let xs: Vec<f32> = (0..num_points)
-            .map(|i| (i as f32) * 2.0 * PI / (num_points as f32))
-            .collect();
-
-        // We need to generate a potentially very large number of lines here: there are N_B
-        // to the power of N_A different cosine curves that we need to consider.
-        // We can generate a list of indices for these curves by getting "permutations with
-        // replacement", which according to
-        // https://docs.rs/itertools/latest/itertools/trait.Itertools.html#method.permutations
-        // is done with the following:
-        let index_choices = repeat_n(0..inner_vertices.len(), outer_vertices.len())
-            .multi_cartesian_product()
-            .collect_vec();
-        // TODO: Generalise this assertion to (N_B)^(N_A)
-        assert_eq!(index_choices.len(), 27);
-
-        let mut yss: Vec<Vec<f32>> = vec![Vec::new(); index_choices.len()];
-        let mut min_ys: Vec<f32> = Vec::new();
-        let mut max_min_y = f32::NEG_INFINITY;
-        let mut max_min_x: Option<f32> = None; // The value of x that maximises `max_min`
-        let mut max_min_indices: Option<Vec<usize>> = None; // TODO: This is messy and I hate it
-
-        for &x in &xs {
-            let mut min_y = f32::INFINITY;
-            let mut min_indices: Option<Vec<usize>> = None;
-            for (index_idx, index_choice) in index_choices.iter().enumerate() {
-                // Each "index choice" is a vector of length N_A, where each element is an
-                // index from 0 to (N_B - 1) indicating which choice of "j" (i.e. which inner
-                // polygon vertex) to use for the corresponding curve.
-                // We then plug all those choices into the dot product with the critical
-                // region's normal vector.
-                // In the triangle case, this is only a single plane with a single normal, so
-                // there is only one dot product, but the number of planes grows with the
-                // number of sides of the polygons (roughly with the cube of N_A).
-                // TODO: Generalise this to N_A > 3
-                assert_eq!(index_choice.len(), 3);
-                let mut y: f32 = 0.0;
-                for (i, &j) in index_choice.iter().enumerate() {
-                    let cos_multiplier = ns[i].dot(inner_vertices[j].to_vec2());
-                    let sin_multiplier =
-                        ns[i].x * inner_vertices[j].y - inner_vertices[j].x * ns[i].y;
-                    let curve_value = cos_multiplier * x.cos() + sin_multiplier * x.sin();
-                    let c_value = cs[i] - curve_value;
-                    let dot_product_term = n_p[i] * c_value;
-                    y += dot_product_term;
-                }
-
-                // Keeping track of the mins and maxes
-                if y < min_y {
-                    min_y = y;
-                    min_indices = Some(index_choice.clone());
-                }
-
-                yss[index_idx].push(y);
-            }
-
-            min_ys.push(min_y);
-            if min_y > max_min_y {
-                max_min_x = Some(x);
-                max_min_y = min_y;
-                max_min_indices = min_indices.clone();
-            }
-        }
-
-        // Our "corner" can be calculated from the max-mins we stored earlier
-        // However, this is done very messily and I want to sort it out
-        let x_1: f32 = {
-            let i = 0;
-            let j = max_min_indices.clone().unwrap()[i];
-            let cos_multiplier = ns[i].dot(inner_vertices[j].to_vec2());
-            let sin_multiplier = ns[i].x * inner_vertices[j].y - inner_vertices[j].x * ns[i].y;
-            let curve_value = cos_multiplier * max_min_x.unwrap().cos()
-                + sin_multiplier * max_min_x.unwrap().sin();
-            cs[i] - curve_value
-        };
-        let x_2: f32 = {
-            let i = 1;
-            let j = max_min_indices.clone().unwrap()[i];
-            let cos_multiplier = ns[i].dot(inner_vertices[j].to_vec2());
-            let sin_multiplier = ns[i].x * inner_vertices[j].y - inner_vertices[j].x * ns[i].y;
-            let curve_value = cos_multiplier * max_min_x.unwrap().cos()
-                + sin_multiplier * max_min_x.unwrap().sin();
-            cs[i] - curve_value
-        };
-        let x_3: f32 = {
-            let i = 2;
-            let j = max_min_indices.clone().unwrap()[i];
-            let cos_multiplier = ns[i].dot(inner_vertices[j].to_vec2());
-            let sin_multiplier = ns[i].x * inner_vertices[j].y - inner_vertices[j].x * ns[i].y;
-            let curve_value = cos_multiplier * max_min_x.unwrap().cos()
-                + sin_multiplier * max_min_x.unwrap().sin();
-            cs[i] - curve_value
-        };
-        let corner: Vector3<f32> = vector![x_1, x_2, x_3];
-        let x_p = corner - (corner.dot(&n_p) * n_p);
-        let n_pseudoinverse: Matrix2x3<f32> = n_mat
-            .pseudo_inverse(0.0001)
-            .expect("Should be able to compute pseudoinverse");
-
-        // Finally!
-        let t: Vector2<f32> = n_pseudoinverse * x_p;
-
-        // If this minimum line ever rises above zero, there is a fit!
-        // By finding the value of x that attains this maximum, we are in some way recording
-        // the "best" fit, for some definition of "best".
-        // I give it a buffer to avoid the floating point flickering that I was getting.
-        let does_fit = max_min_y > 0.01;
-        let fit: Fit = if does_fit {
-            Fit::Fit {
-                angle: max_min_x.unwrap(),
-                translation: t,
-            }
-        } else {
-            Fit::NoFit
-        };
-
-        FitData {
-            fit,
-            plot_data: PlotData { xs, yss, min_ys },
-        }
-    }
+/// Everything a solve depends on, with both sets of vertices centred on their centroids.
+#[derive(Clone, PartialEq)]
+pub struct SolveInputs {
+    pub outer_vertices: Vec<Pos2>,
+    pub inner_vertices: Vec<Pos2>,
+    pub solver: SolverKind,
+    pub settings: SolverSettings,
+}
 
+impl FitCalculator {
     pub fn new() -> Self {
         let outer = Polygon::default();
         let inner = Polygon::with_style(
             Stroke::new(1.0, Color32::from_rgb(200, 25, 100)),
             Color32::from_rgb(100, 50, 150).linear_multiply(0.25),
         );
-        let data = Self::calculate_data(&outer, &inner);
+        let inputs = SolveInputs {
+            outer_vertices: outer.centered_vertices(),
+            inner_vertices: inner.centered_vertices(),
+            solver: SolverKind::default(),
+            settings: SolverSettings::default(),
+        };
+        let data = solve(
+            inputs.solver.solver(),
+            &inputs.outer_vertices,
+            &inputs.inner_vertices,
+            &inputs.settings,
+        );
         Self {
             outer_polygon: outer,
             inner_polygon: inner,
             fit_data: data,
+            solver_settings: inputs.settings.clone(),
+            solver: inputs.solver,
+            solved_inputs: Some(inputs),
+            fit_view: CanvasView::default(),
+            comparison_panel: ComparisonPanel::default(),
+            manual_pose: None,
+            sensitivity_panel: SensitivityPanel::default(),
+            growth_panel: GrowthPanel::default(),
+            sweep_panel: SweepPanel::default(),
+            map_panel: FeasibilityMapPanel::default(),
+            experiment_panel: ExperimentPanel::default(),
+            explorer_panel: ExplorerPanel::default(),
+            saved_scenarios: Vec::new(),
         }
     }
 
@@ -235,30 +113,29 @@ impl FitCalculator {
             Frame::canvas(ui.style()).show(ui, |ui| {
                 self.inner_polygon.ui_content(ui);
             });
-            let poly_fit_display = if let Fit::Fit {
-                angle,
-                translation: t,
-            } = self.fit_data.fit
-            {
-                let translation = Vec2::new(t[0], t[1]);
-
-                // TODO: The PolyFitDisplay also performs centering on the vertices, so we are
-                // duplicating work here. Decide where it makes most sense to perform that
-                // operation, and refactor.
-                PolyFitDisplay::with_fit(
-                    &self.outer_polygon.centered_vertices(),
-                    &self.inner_polygon.centered_vertices(),
-                    angle,
-                    translation,
-                )
-            } else {
-                PolyFitDisplay::without_fit(
-                    &self.outer_polygon.centered_vertices(),
-                    &self.inner_polygon.centered_vertices(),
-                )
-            };
+            let mut poly_fit_display = self.fit_display();
             Frame::canvas(ui.style()).show(ui, |ui| {
-                poly_fit_display.ui_content(ui);
+                poly_fit_display.ui_content(ui, &mut self.fit_view);
+            });
+            self.manual_pose = poly_fit_display.manual_pose();
+            ui.end_row();
+
+            ui.vertical(|ui| {
+                self.outer_polygon.ui_canvas_controls(ui);
+            });
+            ui.vertical(|ui| {
+                self.inner_polygon.ui_canvas_controls(ui);
+                let fits = matches!(self.fit_data.fit, Fit::Fit { .. });
+                CollapsingHeader::new("Grow B")
+                    .id_salt("grow")
+                    .show(ui, |ui| {
+                        self.growth_panel.ui_content(ui, &self.inner_polygon, fits);
+                    });
+            });
+            ui.vertical(|ui| {
+                self.fit_view
+                    .ui_controls(ui, &poly_fit_display.centered_outer_vertices());
+                self.ui_manual_placement(ui, &poly_fit_display);
             });
             ui.end_row();
 
@@ -268,26 +145,848 @@ impl FitCalculator {
             ui.vertical(|ui| {
                 self.inner_polygon.ui_readout(ui);
             });
-            ui.vertical(|ui| {
-                if let Fit::Fit {
-                    angle: _,
-                    translation,
-                } = self.fit_data.fit
+            ui.vertical(|ui| self.ui_fit_summary(ui));
+            ui.end_row();
+        });
+
+        self.growth_panel.grow(
+            ui,
+            &self.outer_polygon,
+            &mut self.inner_polygon,
+            self.solver,
+            &self.solver_settings,
+        );
+
+        let inputs = SolveInputs {
+            outer_vertices: self.outer_polygon.centered_vertices(),
+            inner_vertices: self.inner_polygon.centered_vertices(),
+            solver: self.solver,
+            settings: self.solver_settings.clone(),
+        };
+        if self.solved_inputs.as_ref() != Some(&inputs) {
+            self.fit_data = solve(
+                inputs.solver.solver(),
+                &inputs.outer_vertices,
+                &inputs.inner_vertices,
+                &inputs.settings,
+            );
+            self.solved_inputs = Some(inputs.clone());
+        }
+        self.comparison_panel.update(ui.ctx(), &inputs);
+        self.update_sensitivity(ui, inputs);
+    }
+
+    /// Works out the sensitivity again if it's wanted, and hands it to the polygons to draw.
+    /// While anything is being dragged, or B is being grown, this waits until it's done.
+    fn update_sensitivity(&mut self, ui: &egui::Ui, inputs: SolveInputs) {
+        let busy = ui.input(|i| i.pointer.any_down()) || self.growth_panel.is_growing();
+        self.sensitivity_panel.update(ui.ctx(), inputs, busy);
+
+        let sensitivity = self.sensitivity_panel.sensitivity();
+        self.outer_polygon
+            .set_sensitivity(sensitivity.map(|s| s.outer.as_slice()));
+        self.inner_polygon
+            .set_sensitivity(sensitivity.map(|s| s.inner.as_slice()));
+    }
+
+    /// Sweeps a shape parameter of either polygon, and plots how the fit changes with it.
+    pub fn ui_sweep(&mut self, ui: &mut egui::Ui) {
+        self.sweep_panel.ui_content(
+            ui,
+            &self.outer_polygon.centered_vertices(),
+            &self.inner_polygon.centered_vertices(),
+            self.solver,
+            &self.solver_settings,
+        );
+    }
+
+    /// Maps out where B fits as two shape parameters vary, and loads the polygons from any
+    /// cell of the map that's clicked on, keeping them where they are on the canvas.
+    pub fn ui_feasibility_map(&mut self, ui: &mut egui::Ui) {
+        let loaded = self.map_panel.ui_content(
+            ui,
+            &self.outer_polygon.centered_vertices(),
+            &self.inner_polygon.centered_vertices(),
+            self.solver,
+            &self.solver_settings,
+        );
+        if let Some((outer_vertices, inner_vertices)) = loaded {
+            self.load_polygons(outer_vertices, inner_vertices);
+        }
+    }
+
+    /// Draws random pairs of polygons to see how often B fits, and loads any pair that's
+    /// picked from the list.
+    pub fn ui_experiment(&mut self, ui: &mut egui::Ui) {
+        let picked = self
+            .experiment_panel
+            .ui_content(ui, self.solver, &self.solver_settings);
+        if let Some((outer_vertices, inner_vertices)) = picked {
+            self.load_polygons(outer_vertices, inner_vertices);
+        }
+    }
+
+    /// Searches for the most surprising pairs in which B fits, and opens or saves any of
+    /// the finds.
+    pub fn ui_explorer(&mut self, ui: &mut egui::Ui) {
+        let picked = self
+            .explorer_panel
+            .ui_content(ui, self.solver, &self.solver_settings);
+        match picked {
+            Some((FindAction::Open, find)) => {
+                self.load_polygons(find.outer_vertices, find.inner_vertices);
+            }
+            Some((FindAction::Save { name, solver }, find)) => {
+                let scenario = Scenario {
+                    outer_vertices: placed_like(&self.outer_polygon, find.outer_vertices),
+                    inner_vertices: placed_like(&self.inner_polygon, find.inner_vertices),
+                    solver,
+                    settings: self.solver_settings.clone(),
+                };
+                self.saved_scenarios.push(SavedScenario { name, scenario });
+            }
+            None => {}
+        }
+    }
+
+    /// Replaces both polygons with ones centred on their centroids, keeping them where they
+    /// are on the canvas, and switches to a solver that can handle the new A if need be.
+    fn load_polygons(&mut self, outer_vertices: Vec<Pos2>, inner_vertices: Vec<Pos2>) {
+        let outer_vertices = placed_like(&self.outer_polygon, outer_vertices);
+        let inner_vertices = placed_like(&self.inner_polygon, inner_vertices);
+        self.outer_polygon.set_vertices(outer_vertices);
+        self.inner_polygon.set_vertices(inner_vertices);
+        self.growth_panel.stop();
+        self.solver = supported_solver(self.solver, self.outer_polygon.vertices().len());
+    }
+
+    /// The fit display for the current result, or for B where it's been put by hand.
+    fn fit_display(&self) -> PolyFitDisplay {
+        // TODO: The PolyFitDisplay also performs centering on the vertices, so we are
+        // duplicating work here. Decide where it makes most sense to perform that
+        // operation, and refactor.
+        let outer_vertices = self.outer_polygon.centered_vertices();
+        let inner_vertices = self.inner_polygon.centered_vertices();
+        if let Some(pose) = self.manual_pose {
+            return PolyFitDisplay::with_manual_pose(&outer_vertices, &inner_vertices, pose);
+        }
+
+        if let Fit::Fit {
+            angle,
+            translation: t,
+            active_constraints,
+        } = &self.fit_data.fit
+        {
+            PolyFitDisplay::with_fit(
+                &outer_vertices,
+                &inner_vertices,
+                *angle as f32,
+                Vec2::new(t.x as f32, t.y as f32),
+                self.fit_data.mirrored,
+                active_constraints,
+            )
+        } else if let Some(overflow) = &self.fit_data.overflow {
+            PolyFitDisplay::with_overflow(
+                &outer_vertices,
+                &inner_vertices,
+                overflow.angle as f32,
+                Vec2::new(overflow.translation.x as f32, overflow.translation.y as f32),
+                self.fit_data.mirrored,
+            )
+        } else {
+            PolyFitDisplay::without_fit(&outer_vertices, &inner_vertices)
+        }
+    }
+
+    /// The pose the solver picked, or the one where B pokes out the least if it doesn't fit.
+    fn solver_pose(&self) -> Option<Pose> {
+        let (angle, t) = match (&self.fit_data.fit, &self.fit_data.overflow) {
+            (
+                Fit::Fit {
+                    angle, translation, ..
+                },
+                _,
+            ) => (*angle, *translation),
+            (_, Some(overflow)) => (overflow.angle, overflow.translation),
+            _ => return None,
+        };
+        Some(Pose {
+            angle: angle as f32,
+            translation: Vec2::new(t.x as f32, t.y as f32),
+            mirrored: self.fit_data.mirrored,
+        })
+    }
+
+    /// Lets B be dragged and turned by hand in the fit display, showing which of its vertices
+    /// are outside A.
+    fn ui_manual_placement(&mut self, ui: &mut egui::Ui, display: &PolyFitDisplay) {
+        let mut by_hand = self.manual_pose.is_some();
+        ui.horizontal(|ui| {
+            if ui
+                .checkbox(&mut by_hand, "Place by hand")
+                .on_hover_text("Drag B to move it, and drag the handle above it to turn it")
+                .changed()
+            {
+                self.manual_pose = by_hand.then(|| self.solver_pose().unwrap_or_default());
+            }
+            if by_hand
+                && ui
+                    .add_enabled(
+                        self.solver_pose().is_some(),
+                        egui::Button::new("Snap to solver's pose"),
+                    )
+                    .clicked()
+            {
+                self.manual_pose = self.solver_pose();
+            }
+        });
+
+        let Some(pose) = self.manual_pose else {
+            return;
+        };
+        ui.weak(format!(
+            "Angle: {:.1}°, translation: ({:.2}, {:.2})",
+            pose.angle.to_degrees(),
+            pose.translation.x,
+            pose.translation.y
+        ));
+        let outside = display.vertices_outside();
+        if outside.is_empty() {
+            ui.label("B is inside A");
+        } else {
+            let worst = outside.iter().fold(0.0_f32, |max, &(_, d)| max.max(d));
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!(
+                    "{} of B's vertices outside A, by up to {worst:.3}",
+                    outside.len()
+                ),
+            );
+        }
+    }
+
+    /// How far B is from fitting, at the pose where it pokes out of A the least.
+    fn ui_overflow(&self, ui: &mut egui::Ui) {
+        let Some(overflow) = self.fit_data.overflow else {
+            return;
+        };
+        ui.label(format!(
+            "Overflow: {:.3} at {:.1}°",
+            overflow.depth,
+            overflow.angle.to_degrees()
+        ))
+        .on_hover_text("How far B pokes out past A's edges, at best");
+        ui.weak(format!(
+            "A's edges would need to move out by {:.3}",
+            overflow.depth
+        ));
+        match overflow.scale {
+            Some(scale) if scale > 0.0 => {
+                ui.weak(format!(
+                    "B would need to shrink to {:.1}% of its size",
+                    100.0 * scale
+                ));
+            }
+            Some(_) => {
+                ui.weak("B can't fit at any size with this clearance");
+            }
+            None => {}
+        }
+    }
+
+    /// The clearance B has to be given, and the most it could be given.
+    fn ui_clearance(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Clearance");
+            ui.add(
+                DragValue::new(&mut self.solver_settings.clearance)
+                    .range(0.0..=f64::INFINITY)
+                    .speed(0.1),
+            )
+            .on_hover_text("How far B has to be kept from every edge of A");
+        });
+        if let Some(max_clearance) = self.fit_data.max_clearance {
+            ui.label(format!("Max clearance: {max_clearance:.3}"))
+                .on_hover_text("The largest clearance at which B still fits");
+        }
+    }
+
+    /// The option to flip B over, and which way round it fits better.
+    fn ui_mirroring(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.solver_settings.allow_mirroring, "Allow mirroring")
+            .on_hover_text("Also try B flipped left to right, and keep whichever fits better");
+        let Some(orientations) = self.fit_data.orientations else {
+            return;
+        };
+        let margin =
+            |margin: Option<f64>| margin.map_or_else(|| "-".to_owned(), |m| format!("{m:.3}"));
+        ui.weak(format!(
+            "Best {}: margin {} as drawn, {} mirrored",
+            if self.fit_data.mirrored {
+                "mirrored"
+            } else {
+                "as drawn"
+            },
+            margin(orientations.as_drawn),
+            margin(orientations.mirrored),
+        ));
+    }
+
+    /// The verdict, how it was reached, and the solver options that affect it.
+    fn ui_fit_summary(&mut self, ui: &mut egui::Ui) {
+        let num_outer_vertices = self.outer_polygon.vertices().len();
+        ComboBox::from_label("Solver")
+            .selected_text(self.solver.name())
+            .show_ui(ui, |ui| {
+                for kind in SolverKind::ALL {
+                    ui.add_enabled_ui(kind.supports(num_outer_vertices), |ui| {
+                        ui.selectable_value(&mut self.solver, kind, kind.name())
+                            .on_hover_text(kind.description())
+                            .on_disabled_hover_text("Only handles triangular A");
+                    });
+                }
+            });
+        ComboBox::from_label("Pose")
+            .selected_text(self.solver_settings.objective.name())
+            .show_ui(ui, |ui| {
+                for objective in PoseObjective::ALL {
+                    ui.selectable_value(
+                        &mut self.solver_settings.objective,
+                        objective,
+                        objective.name(),
+                    )
+                    .on_hover_text(objective.description());
+                }
+            });
+
+        if let Fit::Fit {
+            translation,
+            active_constraints,
+            ..
+        } = &self.fit_data.fit
+        {
+            ui.label("Polygon fits!");
+            ui.label(format!("t: ({:.2}, {:.2})", translation.x, translation.y));
+            if let Some(margin) = self.fit_data.margin {
+                ui.label(format!("Margin: {margin:.3}"));
+            }
+            Self::ui_active_constraints(ui, active_constraints, num_outer_vertices);
+        } else if matches!(self.fit_data.fit, Fit::Unsupported) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "The {} solver can't handle A with {num_outer_vertices} vertices",
+                    self.solver.name().to_lowercase()
+                ),
+            );
+        } else {
+            ui.label("No fit");
+            self.ui_overflow(ui);
+        }
+        self.ui_clearance(ui);
+
+        if let Some(check) = self.fit_data.decided_by {
+            ui.weak(format!("Decided by pre-check: {}", check.description()));
+        }
+        ui.checkbox(&mut self.solver_settings.pre_checks, "Quick pre-checks")
+            .on_hover_text("Skip the full sweep when a simple test rules out a fit");
+        if let Some(check) = self.fit_data.exact_check {
+            ui.weak(format!(
+                "Settled exactly at {:.2}°: {}",
+                check.angle.to_degrees(),
+                match check.sign {
+                    Ordering::Greater => "fits",
+                    Ordering::Equal => "fits, touching",
+                    Ordering::Less => "doesn't fit",
+                }
+            ));
+        }
+        ui.checkbox(
+            &mut self.solver_settings.exact,
+            "Exact arithmetic for borderline fits",
+        )
+        .on_hover_text("Redo the sums with rational numbers when the answer is too close to call");
+        self.ui_mirroring(ui);
+
+        ui.add_space(4.0);
+        CollapsingHeader::new("Restrictions")
+            .id_salt("restrictions")
+            .show(ui, |ui| {
+                Self::ui_restrictions(ui, &mut self.solver_settings.restrictions);
+            });
+
+        ui.add_space(4.0);
+        self.ui_sampling(ui);
+
+        ui.add_space(4.0);
+        self.ui_verification(ui);
+
+        ui.add_space(4.0);
+        // The grid search only ever gets close to the best margin, so allow it some leeway
+        let tolerance = 0.02 * f64::from(self.outer_polygon.properties().inradius);
+        self.comparison_panel
+            .ui_content(ui, &self.fit_data, tolerance);
+
+        ui.add_space(4.0);
+        self.sensitivity_panel.ui_content(ui);
+
+        ui.add_space(4.0);
+        self.ui_area_ratio(ui);
+
+        ui.add_space(4.0);
+        Self::ui_feasible_intervals(ui, &self.fit_data);
+    }
+
+    /// Which angles B may be turned to, and where its centroid may be moved to.
+    fn ui_restrictions(ui: &mut egui::Ui, restrictions: &mut Restrictions) {
+        let angles = &mut restrictions.angles;
+        ComboBox::from_label("Rotation")
+            .selected_text(match angles {
+                AngleRestriction::Free => "Free",
+                AngleRestriction::Intervals(_) => "Within intervals",
+                AngleRestriction::Fixed(_) => "Fixed",
+            })
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(*angles == AngleRestriction::Free, "Free")
+                    .clicked()
                 {
-                    ui.label("Polygon fits!");
-                    ui.label(format!("t: ({:.2}, {:.2})", translation.x, translation.y));
-                } else {
-                    ui.label("No fit");
+                    *angles = AngleRestriction::Free;
+                }
+                if ui
+                    .selectable_label(
+                        matches!(angles, AngleRestriction::Intervals(_)),
+                        "Within intervals",
+                    )
+                    .clicked()
+                    && !matches!(angles, AngleRestriction::Intervals(_))
+                {
+                    *angles = AngleRestriction::Intervals(vec![AngleInterval {
+                        start: -20f64.to_radians(),
+                        end: 20f64.to_radians(),
+                    }]);
+                }
+                if ui
+                    .selectable_label(matches!(angles, AngleRestriction::Fixed(_)), "Fixed")
+                    .on_hover_text("Slide B about without turning it")
+                    .clicked()
+                    && !matches!(angles, AngleRestriction::Fixed(_))
+                {
+                    *angles = AngleRestriction::Fixed(0.0);
                 }
             });
-            ui.end_row();
+        match angles {
+            AngleRestriction::Free => {}
+            AngleRestriction::Intervals(intervals) => {
+                let mut removed = None;
+                for (i, interval) in intervals.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        degrees_drag_value(ui, &mut interval.start);
+                        ui.label("to");
+                        degrees_drag_value(ui, &mut interval.end);
+                        if ui.small_button("Remove").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = removed {
+                    intervals.remove(i);
+                }
+                if ui.small_button("Add interval").clicked() {
+                    intervals.push(AngleInterval {
+                        start: 0.0,
+                        end: 90f64.to_radians(),
+                    });
+                }
+            }
+            AngleRestriction::Fixed(angle) => {
+                ui.horizontal(|ui| {
+                    ui.label("Angle");
+                    degrees_drag_value(ui, angle);
+                });
+            }
+        }
+
+        Self::ui_translation_bound(ui, &mut restrictions.translation);
+    }
+
+    /// The region that B's centroid has to stay within, if any.
+    fn ui_translation_bound(ui: &mut egui::Ui, bound: &mut Option<TranslationBound>) {
+        let default_box = Rect::from_center_size(Pos2::ZERO, egui::vec2(20.0, 20.0));
+        ComboBox::from_label("Position")
+            .selected_text(match bound {
+                None => "Anywhere",
+                Some(TranslationBound::Box(_)) => "Within a box",
+                Some(TranslationBound::Polygon(_)) => "Within a polygon",
+            })
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(bound.is_none(), "Anywhere").clicked() {
+                    *bound = None;
+                }
+                if ui
+                    .selectable_label(
+                        matches!(bound, Some(TranslationBound::Box(_))),
+                        "Within a box",
+                    )
+                    .clicked()
+                    && !matches!(bound, Some(TranslationBound::Box(_)))
+                {
+                    let extent = bound.as_ref().map_or(default_box, TranslationBound::extent);
+                    *bound = Some(TranslationBound::Box(extent));
+                }
+                if ui
+                    .selectable_label(
+                        matches!(bound, Some(TranslationBound::Polygon(_))),
+                        "Within a polygon",
+                    )
+                    .on_hover_text("The polygon should be convex")
+                    .clicked()
+                    && !matches!(bound, Some(TranslationBound::Polygon(_)))
+                {
+                    let outline = bound.as_ref().map_or_else(
+                        || TranslationBound::Box(default_box).outline(),
+                        TranslationBound::outline,
+                    );
+                    *bound = Some(TranslationBound::Polygon(outline));
+                }
+            });
+        ui.weak("Measured from A's centroid to B's");
+
+        match bound {
+            None => {}
+            Some(TranslationBound::Box(rect)) => {
+                ui.horizontal(|ui| {
+                    ui.label("x");
+                    ui.add(DragValue::new(&mut rect.min.x).speed(0.5));
+                    ui.label("to");
+                    ui.add(DragValue::new(&mut rect.max.x).speed(0.5));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("y");
+                    ui.add(DragValue::new(&mut rect.min.y).speed(0.5));
+                    ui.label("to");
+                    ui.add(DragValue::new(&mut rect.max.y).speed(0.5));
+                });
+                *rect = Rect::from_two_pos(rect.min, rect.max);
+            }
+            Some(TranslationBound::Polygon(vertices)) => {
+                let mut removed = None;
+                for (i, vertex) in vertices.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}", i + 1));
+                        ui.add(DragValue::new(&mut vertex.x).speed(0.5).prefix("x "));
+                        ui.add(DragValue::new(&mut vertex.y).speed(0.5).prefix("y "));
+                        if ui.small_button("Remove").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = removed
+                    && vertices.len() > 3
+                {
+                    vertices.remove(i);
+                }
+                if ui.small_button("Add vertex").clicked()
+                    && let (Some(&first), Some(&last)) = (vertices.first(), vertices.last())
+                {
+                    vertices.push(first.lerp(last, 0.5));
+                }
+            }
+        }
+    }
+
+    /// How the angles are sampled, and how many samples that came to.
+    fn ui_sampling(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.solver_settings.adaptive, "Adaptive sampling")
+                .on_hover_text("Sample more finely around kinks and zero crossings");
+            ui.weak(format!("{} samples", self.fit_data.plot_data.xs.len()));
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.solver_settings.use_symmetry, "Use symmetry")
+                .on_hover_text("Only sweep the angles that the polygons' symmetries make distinct");
+            let domain = self.fit_data.plot_data.domain;
+            if domain.length < TAU {
+                ui.weak(format!(
+                    "{:.1}°–{:.1}°",
+                    domain.start.to_degrees(),
+                    (domain.start + domain.length).to_degrees()
+                ));
+            }
+        });
+        ui.add_enabled(
+            self.solver_settings.adaptive,
+            Slider::new(&mut self.solver_settings.tolerance, 1e-6..=1.0)
+                .logarithmic(true)
+                .text("tolerance"),
+        )
+        .on_hover_text("How far the Minimum line may stray from the true curve between samples");
+    }
+
+    /// The switch for proving the answer, and the proof if there is one.
+    fn ui_verification(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.solver_settings.verify, "Prove the answer")
+                .on_hover_text("Bound every curve with interval arithmetic to prove fit or no fit");
+            ui.add_enabled(
+                self.solver_settings.verify,
+                DragValue::new(&mut self.solver_settings.verify_depth)
+                    .range(0..=24)
+                    .prefix("depth "),
+            )
+            .on_hover_text("How many times each piece of the angles may be halved");
+        });
+
+        let settings = &self.solver_settings;
+        let Some(certificate) = &self.fit_data.certificate else {
+            if !settings.verify || matches!(self.fit_data.fit, Fit::Unsupported) {
+                return;
+            }
+            // The proof only knows about the critical region, without anything in the way
+            ui.weak(
+                if settings.restrictions.is_active() || settings.clearance > 0.0 {
+                    "Proof unavailable with restrictions or clearance"
+                } else {
+                    "Proof only available with the critical region solver"
+                },
+            );
+            return;
+        };
+        ui.label(match certificate.verdict {
+            Verdict::ProvenFit { angle } => {
+                format!("Proven fit at {:.4}°", angle.to_degrees())
+            }
+            verdict => capitalised(verdict.description()),
+        });
+        CollapsingHeader::new(format!(
+            "Certificate ({} pieces)",
+            certificate.entries.len()
+        ))
+        .id_salt("certificate")
+        .show(ui, |ui| {
+            if ui.small_button("Copy as text").clicked() {
+                ui.ctx().copy_text(certificate.to_text());
+            }
+            ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                Grid::new("certificate_entries")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Start");
+                        ui.label("End");
+                        ui.label("Lower");
+                        ui.label("Upper");
+                        ui.end_row();
+
+                        for entry in &certificate.entries {
+                            ui.label(format!("{:.4}°", entry.start.to_degrees()));
+                            ui.label(format!("{:.4}°", entry.end.to_degrees()));
+                            ui.label(format!("{:.3e}", entry.lower));
+                            ui.label(format!("{:.3e}", entry.upper));
+                            ui.end_row();
+                        }
+                    });
+            });
         });
+    }
 
-        // Recalculate
-        self.fit_data = Self::calculate_data(&self.outer_polygon, &self.inner_polygon);
+    /// B can only fit inside A if it's no bigger than A, so the ratio of their areas gives
+    /// a quick sanity check on the solver.
+    fn ui_area_ratio(&self, ui: &mut egui::Ui) {
+        let outer_area = self.outer_polygon.properties().area;
+        let inner_area = self.inner_polygon.properties().area;
+        let ratio = inner_area / outer_area;
+        ui.label(format!("Area ratio B/A: {ratio:.3}"));
+        if ratio > 1.0 {
+            ui.weak("B is larger than A, so it can't fit");
+        }
+    }
+
+    fn ui_active_constraints(
+        ui: &mut egui::Ui,
+        active_constraints: &[ActiveConstraint],
+        num_outer_vertices: usize,
+    ) {
+        ui.label("Contacts:");
+        for constraint in active_constraints {
+            let from = constraint
+                .edge
+                .checked_sub(1)
+                .unwrap_or(num_outer_vertices.saturating_sub(1));
+            ui.label(format!(
+                "A{}–A{} / B{}: slack {:.2}",
+                from + 1,
+                constraint.edge + 1,
+                constraint.vertex + 1,
+                constraint.slack
+            ));
+        }
+    }
+
+    fn ui_feasible_intervals(ui: &mut egui::Ui, data: &FitData) {
+        let intervals = &data.feasible_intervals;
+        if intervals.is_empty() {
+            // B can fit without a range of angles to show, when it only just fits and the
+            // exact check settled it
+            match data.fit {
+                Fit::Fit { .. } => {
+                    ui.weak("Fits at too narrow a range of angles to show");
+                }
+                Fit::NoFit => {
+                    ui.label("No feasible angles");
+                }
+                Fit::Unsupported => {}
+            }
+            return;
+        }
+
+        ui.label("Feasible angles:");
+        Grid::new("feasible_intervals")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Start");
+                ui.label("End");
+                ui.label("Peak at");
+                ui.label("Margin");
+                ui.end_row();
+
+                for interval in intervals {
+                    ui.label(format!("{:.1}°", interval.start.to_degrees()));
+                    ui.label(format!("{:.1}°", interval.end.to_degrees()));
+                    ui.label(format!("{:.1}°", interval.peak_angle.to_degrees()));
+                    ui.label(format!("{:.2}", interval.peak_margin));
+                    ui.end_row();
+                }
+            });
     }
 
     pub fn plot_data(&self) -> PlotData {
         self.fit_data.plot_data.clone()
     }
+
+    pub fn feasible_intervals(&self) -> &[FeasibleInterval] {
+        &self.fit_data.feasible_intervals
+    }
+
+    pub fn restrictions(&self) -> &Restrictions {
+        &self.solver_settings.restrictions
+    }
+
+    /// The polygons and settings as they stand, to be saved.
+    pub fn scenario(&self) -> Scenario {
+        Scenario {
+            outer_vertices: self.outer_polygon.vertices().to_vec(),
+            inner_vertices: self.inner_polygon.vertices().to_vec(),
+            solver: self.solver,
+            settings: self.solver_settings.clone(),
+        }
+    }
+
+    pub fn saved_scenarios(&self) -> &[SavedScenario] {
+        &self.saved_scenarios
+    }
+
+    pub fn set_saved_scenarios(&mut self, saved_scenarios: Vec<SavedScenario>) {
+        self.saved_scenarios = saved_scenarios;
+    }
+
+    /// The scenarios that have been kept, each of which can be renamed, loaded or deleted,
+    /// and a button to keep the polygons and settings as they stand.
+    pub fn ui_saved_scenarios(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Save the current polygons").clicked() {
+            self.saved_scenarios.push(SavedScenario {
+                name: format!("Scenario {}", self.saved_scenarios.len() + 1),
+                scenario: self.scenario(),
+            });
+        }
+
+        let mut to_load = None;
+        let mut to_delete = None;
+        Grid::new("saved_scenarios").striped(true).show(ui, |ui| {
+            for (i, saved) in self.saved_scenarios.iter_mut().enumerate() {
+                ui.text_edit_singleline(&mut saved.name);
+                ui.label(format!(
+                    "{} in {}",
+                    saved.scenario.inner_vertices.len(),
+                    saved.scenario.outer_vertices.len()
+                ))
+                .on_hover_text("How many vertices B and A have");
+                if ui.button("Load").clicked() {
+                    to_load = Some(i);
+                }
+                if ui.button("Delete").clicked() {
+                    to_delete = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(saved) = to_load.and_then(|i| self.saved_scenarios.get(i)) {
+            let scenario = saved.scenario.clone();
+            self.load_scenario(&scenario);
+            self.growth_panel.stop();
+        }
+        if let Some(i) = to_delete.filter(|&i| i < self.saved_scenarios.len()) {
+            self.saved_scenarios.remove(i);
+        }
+    }
+
+    /// Puts the polygons and settings back as they were saved. Polygons with too few
+    /// vertices to be worth solving are left as they are, and if the saved solver can't
+    /// handle A, another one is used instead.
+    pub fn load_scenario(&mut self, scenario: &Scenario) {
+        if scenario.outer_vertices.len() >= 3 {
+            self.outer_polygon
+                .set_vertices(scenario.outer_vertices.clone());
+        }
+        if scenario.inner_vertices.len() >= 3 {
+            self.inner_polygon
+                .set_vertices(scenario.inner_vertices.clone());
+        }
+        self.solver = supported_solver(scenario.solver, self.outer_polygon.vertices().len());
+        self.solver_settings = scenario.settings.clone();
+    }
+}
+
+/// Everything needed to set the calculator up again: the two polygons, and how the fit
+/// between them is found.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub outer_vertices: Vec<Pos2>,
+    pub inner_vertices: Vec<Pos2>,
+    pub solver: SolverKind,
+    pub settings: SolverSettings,
+}
+
+/// A [`Scenario`] that's been kept under a name, to be loaded again later.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SavedScenario {
+    pub name: String,
+    pub scenario: Scenario,
+}
+
+/// `solver` if it can handle an outer polygon with `num_outer_vertices` vertices, or else the
+/// first solver that can.
+fn supported_solver(solver: SolverKind, num_outer_vertices: usize) -> SolverKind {
+    if solver.supports(num_outer_vertices) {
+        return solver;
+    }
+    SolverKind::ALL
+        .into_iter()
+        .find(|kind| kind.supports(num_outer_vertices))
+        .unwrap_or(solver)
+}
+
+/// `vertices`, centred on their centroid, moved to where `polygon` is centred.
+fn placed_like(polygon: &Polygon, vertices: Vec<Pos2>) -> Vec<Pos2> {
+    let centroid = polygon.centroid().to_vec2();
+    vertices.into_iter().map(|v| v + centroid).collect()
+}
+
+fn capitalised(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
 }
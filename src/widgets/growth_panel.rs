@@ -0,0 +1,121 @@
+use egui::{ComboBox, Vec2};
+
+use crate::{
+    fit::{ShapeConstraints, ShapeObjective, ShapeOptimiser, SolverKind, SolverSettings},
+    widgets::polygon::Polygon,
+};
+
+/// Grows B as much as it can while it still fits, keeping to the chosen constraints, a move
+/// each frame so that it can be watched. Each move tried means solving again, so trying only
+/// one a frame keeps the app responsive.
+#[derive(Default)]
+pub struct GrowthPanel {
+    objective: ShapeObjective,
+    constraints: ShapeConstraints,
+    /// The optimiser growing B, if it's been started, along with where B's centroid was when
+    /// it started.
+    growth: Option<(ShapeOptimiser, Vec2)>,
+}
+
+impl GrowthPanel {
+    pub fn is_growing(&self) -> bool {
+        self.growth
+            .as_ref()
+            .is_some_and(|(optimiser, _)| !optimiser.is_finished())
+    }
+
+    /// Forgets the optimiser, as when B has been replaced.
+    pub fn stop(&mut self) {
+        self.growth = None;
+    }
+
+    /// Takes the optimiser another move further, if it's running, and shows B as it's got to
+    /// so far.
+    pub fn grow(
+        &mut self,
+        ui: &egui::Ui,
+        outer_polygon: &Polygon,
+        inner_polygon: &mut Polygon,
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) {
+        if !self.is_growing() {
+            return;
+        }
+        let Some((optimiser, offset)) = &mut self.growth else {
+            return;
+        };
+
+        let outer_vertices = outer_polygon.centered_vertices();
+        optimiser.try_next_move(solver.solver(), &outer_vertices, settings);
+        inner_polygon.set_vertices(optimiser.vertices().iter().map(|&v| v + *offset).collect());
+        ui.ctx().request_repaint();
+    }
+
+    /// The objective and constraints, and the button to start or stop. B has to `fit` to
+    /// begin with.
+    pub fn ui_content(&mut self, ui: &mut egui::Ui, inner_polygon: &Polygon, fits: bool) {
+        ComboBox::from_label("Maximise")
+            .selected_text(self.objective.name())
+            .show_ui(ui, |ui| {
+                for objective in ShapeObjective::ALL {
+                    ui.selectable_value(&mut self.objective, objective, objective.name())
+                        .on_hover_text(objective.description());
+                }
+            });
+
+        let constraints = &mut self.constraints;
+        ui.checkbox(&mut constraints.fixed_angles, "Keep angles")
+            .on_hover_text("Only the lengths of B's edges may change");
+        ui.checkbox(&mut constraints.fixed_aspect, "Keep aspect ratio")
+            .on_hover_text("Keep the shape of B's bounding box");
+        let num_vertices = inner_polygon.vertices().len();
+        let edge_name = |edge: usize| {
+            let from = edge
+                .checked_sub(1)
+                .unwrap_or(num_vertices.saturating_sub(1));
+            format!("B{}–B{}", from + 1, edge + 1)
+        };
+        constraints.fixed_edge = constraints.fixed_edge.filter(|&edge| edge < num_vertices);
+        ComboBox::from_label("Keep edge length")
+            .selected_text(constraints.fixed_edge.map_or("None".to_owned(), edge_name))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut constraints.fixed_edge, None, "None");
+                for edge in 0..num_vertices {
+                    ui.selectable_value(&mut constraints.fixed_edge, Some(edge), edge_name(edge));
+                }
+            });
+
+        ui.horizontal(|ui| {
+            if self.is_growing() {
+                if ui.button("Stop").clicked() {
+                    self.growth = None;
+                }
+            } else if ui
+                .add_enabled(fits, egui::Button::new("Grow"))
+                .on_disabled_hover_text("B has to fit to begin with")
+                .clicked()
+            {
+                let optimiser = ShapeOptimiser::new(
+                    &inner_polygon.centered_vertices(),
+                    self.objective,
+                    self.constraints.clone(),
+                );
+                self.growth = Some((optimiser, inner_polygon.centroid().to_vec2()));
+            }
+        });
+        if let Some((optimiser, _)) = &self.growth {
+            ui.weak(format!(
+                "{}: {:.2} after {} moves{}",
+                optimiser.objective().name(),
+                optimiser.value(),
+                optimiser.accepted(),
+                if optimiser.is_finished() {
+                    ", done"
+                } else {
+                    ""
+                }
+            ));
+        }
+    }
+}
@@ -1,12 +1,35 @@
 use egui::{
-    Color32, Pos2, Rect, Sense, Stroke, Vec2,
-    emath::{self, Rot2},
+    Align2, Color32, FontId, Painter, Pos2, Rect, Sense, Shape, Stroke, Vec2,
+    emath::{RectTransform, Rot2},
     epaint::PathShape,
+    vec2,
 };
 
+use crate::{
+    fit::ActiveConstraint,
+    geometry::{clip_to_half_plane, edge_half_planes},
+    widgets::canvas_view::CanvasView,
+};
+
+/// Where the inner polygon is put, relative to the outer polygon's centroid.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pose {
+    pub angle: f32,
+    pub translation: Vec2,
+    /// Whether the inner polygon is flipped left to right before it's turned.
+    pub mirrored: bool,
+}
+
 pub enum PolyFitResult {
     NoFit,
-    Fit { angle: f32, translation: Vec2 },
+    Fit {
+        pose: Pose,
+        active_constraints: Vec<ActiveConstraint>,
+    },
+    /// B doesn't fit, so it's shown where it pokes out of A the least.
+    Overflow(Pose),
+    /// B has been put in place by hand, and can be dragged and turned.
+    Manual(Pose),
 }
 
 pub struct PolyFitDisplay {
@@ -16,9 +39,9 @@ pub struct PolyFitDisplay {
     outer_fill: Color32,
     inner_stroke: Stroke,
     inner_fill: Color32,
+    contact_stroke: Stroke,
+    overflow_fill: Color32,
     fit: PolyFitResult,
-
-    canvas_size: f32,
 }
 
 impl PolyFitDisplay {
@@ -27,20 +50,69 @@ impl PolyFitDisplay {
         inner_vertices: &[Pos2],
         angle: f32,
         translation: Vec2,
+        mirrored: bool,
+        active_constraints: &[ActiveConstraint],
     ) -> Self {
-        Self {
-            outer_vertices: outer_vertices.to_vec(),
-            inner_vertices: inner_vertices.to_vec(),
-            outer_stroke: Stroke::new(1.0, Color32::from_rgb(25, 200, 100)),
-            outer_fill: Color32::from_rgb(50, 100, 150).linear_multiply(0.25),
-            inner_stroke: Stroke::new(1.0, Color32::from_rgb(200, 25, 100)),
-            inner_fill: Color32::from_rgb(100, 50, 150).linear_multiply(0.25),
-            fit: PolyFitResult::Fit { angle, translation },
-            canvas_size: 200.0,
+        Self::with_result(
+            outer_vertices,
+            inner_vertices,
+            PolyFitResult::Fit {
+                pose: Pose {
+                    angle,
+                    translation,
+                    mirrored,
+                },
+                active_constraints: active_constraints.to_vec(),
+            },
+        )
+    }
+
+    /// Shows B where it pokes out of A the least, with the parts outside A picked out.
+    pub fn with_overflow(
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        angle: f32,
+        translation: Vec2,
+        mirrored: bool,
+    ) -> Self {
+        Self::with_result(
+            outer_vertices,
+            inner_vertices,
+            PolyFitResult::Overflow(Pose {
+                angle,
+                translation,
+                mirrored,
+            }),
+        )
+    }
+
+    /// Shows B in a pose picked by hand, with handles to move and turn it.
+    pub fn with_manual_pose(outer_vertices: &[Pos2], inner_vertices: &[Pos2], pose: Pose) -> Self {
+        Self::with_result(outer_vertices, inner_vertices, PolyFitResult::Manual(pose))
+    }
+
+    /// The pose picked by hand, after any dragging this frame.
+    pub fn manual_pose(&self) -> Option<Pose> {
+        match self.fit {
+            PolyFitResult::Manual(pose) => Some(pose),
+            _ => None,
+        }
+    }
+
+    fn pose(&self) -> Option<Pose> {
+        match self.fit {
+            PolyFitResult::NoFit => None,
+            PolyFitResult::Fit { pose, .. }
+            | PolyFitResult::Overflow(pose)
+            | PolyFitResult::Manual(pose) => Some(pose),
         }
     }
 
     pub fn without_fit(outer_vertices: &[Pos2], inner_vertices: &[Pos2]) -> Self {
+        Self::with_result(outer_vertices, inner_vertices, PolyFitResult::NoFit)
+    }
+
+    fn with_result(outer_vertices: &[Pos2], inner_vertices: &[Pos2], fit: PolyFitResult) -> Self {
         Self {
             outer_vertices: outer_vertices.to_vec(),
             inner_vertices: inner_vertices.to_vec(),
@@ -48,88 +120,261 @@ impl PolyFitDisplay {
             outer_fill: Color32::from_rgb(50, 100, 150).linear_multiply(0.25),
             inner_stroke: Stroke::new(1.0, Color32::from_rgb(200, 25, 100)),
             inner_fill: Color32::from_rgb(100, 50, 150).linear_multiply(0.25),
-            fit: PolyFitResult::NoFit,
-            canvas_size: 200.0,
+            contact_stroke: Stroke::new(2.5, Color32::from_rgb(230, 180, 50)),
+            overflow_fill: Color32::from_rgb(230, 50, 50).linear_multiply(0.6),
+            fit,
         }
     }
 
-    fn centered_outer_vertices(&self) -> Vec<Pos2> {
+    pub fn centered_outer_vertices(&self) -> Vec<Pos2> {
         let mut centroid = Vec2::ZERO;
         for a in &self.outer_vertices {
             centroid += a.to_vec2();
         }
         centroid /= self.outer_vertices.len() as f32;
-        let offset = Vec2::splat(self.canvas_size / 2.0) - centroid;
 
-        self.outer_vertices.iter().map(|&a| a + offset).collect()
+        self.outer_vertices.iter().map(|&a| a - centroid).collect()
     }
 
     fn transformed_inner_vertices(&self) -> Option<Vec<Pos2>> {
-        match self.fit {
-            PolyFitResult::NoFit => None,
-            PolyFitResult::Fit { angle, translation } => {
-                let mut centroid = Vec2::ZERO;
-                for b in &self.inner_vertices {
-                    centroid += b.to_vec2();
-                }
-                centroid /= self.inner_vertices.len() as f32;
-
-                // The maths is a bit different to the outer vertices, because we need to center
-                // the shape at 0 (rather than the centre of the frame), then perform the rotation,
-                // _then_ the translation, and only then can we offset it back to the centre of
-                // the frame.
-                let result = self
-                    .inner_vertices
-                    .iter()
-                    .map(|&a| {
-                        let centered = a.to_vec2() - centroid;
-                        let rotated = Rot2::from_angle(-angle) * centered;
-                        let translated = rotated + translation;
-                        let centered_in_frame = translated + Vec2::splat(self.canvas_size / 2.0);
-                        centered_in_frame.to_pos2()
-                    })
-                    .collect();
-                Some(result)
-            }
+        let Pose {
+            angle,
+            translation,
+            mirrored,
+        } = self.pose()?;
+
+        let mut centroid = Vec2::ZERO;
+        for b in &self.inner_vertices {
+            centroid += b.to_vec2();
         }
+        centroid /= self.inner_vertices.len() as f32;
+
+        // We need to center the shape at 0 before performing the rotation, and only
+        // _then_ the translation. Any mirroring happens before all of that.
+        let result = self
+            .inner_vertices
+            .iter()
+            .map(|&a| {
+                let mut centered = a.to_vec2() - centroid;
+                if mirrored {
+                    centered.x = -centered.x;
+                }
+                let rotated = Rot2::from_angle(-angle) * centered;
+                let translated = rotated + translation;
+                translated.to_pos2()
+            })
+            .collect();
+        Some(result)
     }
 
-    pub fn ui_content(&self, ui: &mut egui::Ui) -> egui::Response {
-        let (response, painter) = ui.allocate_painter(
-            Vec2::new(self.canvas_size, self.canvas_size),
-            Sense::hover(),
-        );
+    /// How far each of the placed inner polygon's vertices lies outside the outer polygon,
+    /// for those that do, by index.
+    pub fn vertices_outside(&self) -> Vec<(usize, f32)> {
+        let planes = edge_half_planes(&self.centered_outer_vertices());
+        self.transformed_inner_vertices()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter_map(|(j, b)| {
+                let distance = planes
+                    .iter()
+                    .map(|&(n, c)| n.dot(b.to_vec2()) - c)
+                    .fold(f32::NEG_INFINITY, f32::max);
+                (distance > 0.0).then_some((j, distance))
+            })
+            .collect()
+    }
 
-        let to_screen = emath::RectTransform::from_to(
-            Rect::from_min_size(Pos2::ZERO, response.rect.size()),
-            response.rect,
-        );
+    /// Draws the fit into a canvas looking through `view`, which is kept by the caller
+    /// because the display itself is rebuilt every frame.
+    ///
+    /// If B was placed by hand, it can be dragged about and turned, and [`Self::manual_pose`]
+    /// gives where it ended up.
+    pub fn ui_content(&mut self, ui: &mut egui::Ui, view: &mut CanvasView) -> egui::Response {
+        let (response, painter) = view.allocate_painter(ui);
+        let to_screen = view.to_screen(response.rect);
+        let manual_handles = self.manual_handles(ui, &response, to_screen);
 
         let screen_outer_points: Vec<Pos2> = self
             .centered_outer_vertices()
             .iter()
             .map(|&a| to_screen * a)
             .collect();
-        let outer_shape =
-            PathShape::convex_polygon(screen_outer_points, self.outer_fill, self.outer_stroke);
+        let outer_shape = PathShape::convex_polygon(
+            screen_outer_points.clone(),
+            self.outer_fill,
+            self.outer_stroke,
+        );
         painter.add(outer_shape);
 
-        if let PolyFitResult::Fit {
-            angle: _,
-            translation: _,
-        } = self.fit
-        {
-            let screen_inner_points: Vec<Pos2> = self
-                .transformed_inner_vertices()
-                .unwrap()
-                .iter()
-                .map(|&b| to_screen * b)
-                .collect();
-            let inner_shape =
-                PathShape::convex_polygon(screen_inner_points, self.inner_fill, self.inner_stroke);
+        if let Some(inner_vertices) = self.transformed_inner_vertices() {
+            let screen_inner_points: Vec<Pos2> =
+                inner_vertices.iter().map(|&b| to_screen * b).collect();
+            let inner_shape = PathShape::convex_polygon(
+                screen_inner_points.clone(),
+                self.inner_fill,
+                self.inner_stroke,
+            );
             painter.add(inner_shape);
+
+            match &self.fit {
+                PolyFitResult::Fit {
+                    active_constraints, ..
+                } => self.paint_contacts(
+                    &painter,
+                    &screen_outer_points,
+                    &screen_inner_points,
+                    active_constraints,
+                ),
+                PolyFitResult::Overflow(_) => {
+                    self.paint_overflow(&painter, &inner_vertices, |p| to_screen * p);
+                }
+                PolyFitResult::Manual(_) => {
+                    self.paint_overflow(&painter, &inner_vertices, |p| to_screen * p);
+                    self.paint_vertices_outside(&painter, &screen_inner_points);
+                }
+                PolyFitResult::NoFit => {}
+            }
         }
+        painter.extend(manual_handles);
+
+        view.paint_cursor_readout(ui, &painter, response.rect);
 
         response
     }
+
+    /// Lets the user drag a hand-placed inner polygon about, and turn it with a handle above
+    /// it, and returns the shapes to draw for the handles.
+    fn manual_handles(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        to_screen: RectTransform,
+    ) -> Vec<Shape> {
+        let handle_radius = 5.0;
+        let rotate_handle_offset = 20.0;
+
+        let Some(inner_vertices) = self.transformed_inner_vertices() else {
+            return Vec::new();
+        };
+        let PolyFitResult::Manual(pose) = &mut self.fit else {
+            return Vec::new();
+        };
+
+        let bounds = to_screen.transform_rect(Rect::from_points(&inner_vertices));
+        let centre = to_screen * pose.translation.to_pos2();
+        let rotate_handle = bounds.center_top() - vec2(0.0, rotate_handle_offset);
+
+        // Dragging anywhere on B moves it. This goes first, so the rotate handle is on top.
+        let move_response = ui.interact(bounds, response.id.with("manual_move"), Sense::drag());
+        if move_response.dragged() {
+            pose.translation += move_response.drag_delta() / to_screen.scale();
+        }
+
+        let handle_rect = Rect::from_center_size(rotate_handle, Vec2::splat(2.0 * handle_radius));
+        let rotate_response = ui.interact(
+            handle_rect,
+            response.id.with("manual_rotate"),
+            Sense::drag(),
+        );
+        if let (true, Some(pointer)) = (
+            rotate_response.dragged(),
+            rotate_response.interact_pointer_pos(),
+        ) {
+            // B is drawn turned through `-angle`, so turning it on screen takes away from it.
+            // The handle stays above B's bounding box rather than turning with it, so B is
+            // turned by however far the pointer has moved since the last frame.
+            let from = pointer - rotate_response.drag_delta() - centre;
+            let to = pointer - centre;
+            pose.angle -= to.angle() - from.angle();
+        }
+
+        let stroke = ui.style().interact(&rotate_response).fg_stroke;
+        vec![
+            Shape::line_segment(
+                [bounds.center_top(), rotate_handle],
+                ui.style().interact(&move_response).fg_stroke,
+            ),
+            Shape::circle_stroke(rotate_handle, handle_radius, stroke),
+        ]
+    }
+
+    /// Marks each inner vertex that lies outside the outer polygon, labelled with how far out
+    /// it is.
+    fn paint_vertices_outside(&self, painter: &Painter, screen_inner_points: &[Pos2]) {
+        for (j, distance) in self.vertices_outside() {
+            let Some(&vertex) = screen_inner_points.get(j) else {
+                continue;
+            };
+            painter.circle_filled(vertex, 3.0, self.overflow_fill);
+            painter.text(
+                vertex + Vec2::new(4.0, -4.0),
+                Align2::LEFT_BOTTOM,
+                format!("B{}: {distance:.2}", j + 1),
+                FontId::monospace(10.0),
+                self.overflow_fill,
+            );
+        }
+    }
+
+    /// Fills in the parts of the placed inner polygon that lie outside each outer edge.
+    fn paint_overflow(
+        &self,
+        painter: &Painter,
+        inner_vertices: &[Pos2],
+        to_screen: impl Fn(Pos2) -> Pos2,
+    ) {
+        for (n, c) in edge_half_planes(&self.centered_outer_vertices()) {
+            // Keep the part of B past the edge, cutting away the part inside it
+            let outside = clip_to_half_plane(
+                inner_vertices,
+                |p| c - n.dot(p.to_vec2()),
+                |a, b, t| a.lerp(b, t),
+            );
+            if outside.len() < 3 {
+                continue;
+            }
+            let points = outside.into_iter().map(&to_screen).collect();
+            painter.add(PathShape::convex_polygon(
+                points,
+                self.overflow_fill,
+                Stroke::NONE,
+            ));
+        }
+    }
+
+    /// Highlights each outer edge and inner vertex that are pressed up against each other,
+    /// labelled with how much room is left between them.
+    fn paint_contacts(
+        &self,
+        painter: &Painter,
+        screen_outer_points: &[Pos2],
+        screen_inner_points: &[Pos2],
+        active_constraints: &[ActiveConstraint],
+    ) {
+        for constraint in active_constraints {
+            // Edge `i` runs from outer vertex `i - 1` to outer vertex `i`
+            let from_idx = constraint
+                .edge
+                .checked_sub(1)
+                .unwrap_or(screen_outer_points.len().saturating_sub(1));
+            let (Some(&from), Some(&to), Some(&vertex)) = (
+                screen_outer_points.get(from_idx),
+                screen_outer_points.get(constraint.edge),
+                screen_inner_points.get(constraint.vertex),
+            ) else {
+                continue;
+            };
+
+            painter.line_segment([from, to], self.contact_stroke);
+            painter.circle_filled(vertex, 3.0, self.contact_stroke.color);
+            painter.text(
+                vertex + Vec2::new(4.0, -4.0),
+                Align2::LEFT_BOTTOM,
+                format!("{:.2}", constraint.slack),
+                FontId::monospace(10.0),
+                self.contact_stroke.color,
+            );
+        }
+    }
 }
@@ -1,14 +1,99 @@
 use egui::{
-    Color32, Pos2, Rect, Sense, Shape, Stroke, Vec2, emath,
+    Color32, DragValue, Grid, Pos2, Rect, Sense, Shape, Stroke, Vec2,
+    emath::{RectTransform, Rot2},
     epaint::{self, PathShape},
-    pos2,
+    pos2, vec2,
 };
+use itertools::Itertools as _;
+
+use crate::{
+    fit::VertexSensitivity, geometry::PolygonProperties, widgets::canvas_view::CanvasView,
+};
+
+/// The smallest factor the transform controls and handles will scale by, so that a polygon
+/// can't be squashed flat (or turned inside out) by dragging the factor through zero.
+const MIN_SCALE: f32 = 0.01;
+
+/// How dragged vertices are pulled into place. Holding Shift while dragging ignores all of
+/// this.
+struct Snapping {
+    to_grid: bool,
+    to_angle: bool,
+    /// The angle step, in degrees, that edges into a dragged vertex are snapped to.
+    angle_step: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            to_grid: false,
+            to_angle: false,
+            angle_step: 15.0,
+        }
+    }
+}
+
+impl Snapping {
+    /// Snaps `point` to the grid, and then turns it about `previous` so that the edge between
+    /// them lies at a multiple of the angle step.
+    fn apply(&self, point: Pos2, previous: Option<Pos2>, grid_spacing: f32) -> Pos2 {
+        let mut point = point;
+        if self.to_grid {
+            point = (point / grid_spacing).round() * grid_spacing;
+        }
+        if let (true, Some(previous)) = (self.to_angle, previous) {
+            let edge = point - previous;
+            let step = self.angle_step.to_radians();
+            let angle = (edge.angle() / step).round() * step;
+            point = previous + edge.length() * Vec2::angled(angle);
+        }
+        point
+    }
+}
+
+/// The values typed into the numeric transform controls, kept between frames.
+struct TransformInputs {
+    /// In degrees, clockwise on screen.
+    angle: f32,
+    scale: Vec2,
+    uniform_scale: bool,
+    offset: Vec2,
+}
+
+impl Default for TransformInputs {
+    fn default() -> Self {
+        Self {
+            angle: 15.0,
+            scale: Vec2::splat(1.0),
+            uniform_scale: true,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+/// What dragging one of the handles around the bounding box does to the whole polygon.
+#[derive(Clone, Copy)]
+enum TransformHandle {
+    Move,
+    Rotate,
+    ScaleUniform,
+    ScaleX,
+    ScaleY,
+}
 
 pub struct Polygon {
     vertices: Vec<Pos2>,
     stroke: Stroke,
     fill: Color32,
     bounding_box_stroke: Stroke,
+    view: CanvasView,
+    snapping: Snapping,
+    /// Where the vertex being dragged sits relative to the pointer that grabbed it.
+    drag_offset: Vec2,
+    show_transform_handles: bool,
+    transform_inputs: TransformInputs,
+    /// How much each vertex matters to the fit, drawn as arrows and tolerance circles.
+    sensitivity: Vec<VertexSensitivity>,
 }
 
 impl Default for Polygon {
@@ -24,6 +109,12 @@ impl Default for Polygon {
             stroke: Stroke::new(1.0, Color32::from_rgb(25, 200, 100)),
             fill: Color32::from_rgb(50, 100, 150).linear_multiply(0.25),
             bounding_box_stroke: Stroke::new(0.0, Color32::LIGHT_GREEN.linear_multiply(0.25)),
+            view: CanvasView::new(pos2(offset, offset)),
+            snapping: Snapping::default(),
+            drag_offset: Vec2::ZERO,
+            show_transform_handles: false,
+            transform_inputs: TransformInputs::default(),
+            sensitivity: Vec::new(),
         }
     }
 }
@@ -37,47 +128,135 @@ impl Polygon {
         }
     }
 
-    pub fn centered_vertices(&self) -> Vec<Pos2> {
+    pub fn vertices(&self) -> &[Pos2] {
+        &self.vertices
+    }
+
+    pub fn set_vertices(&mut self, vertices: Vec<Pos2>) {
+        self.vertices = vertices;
+    }
+
+    /// Shows how much each vertex matters to the fit, or stops showing it.
+    pub fn set_sensitivity(&mut self, sensitivity: Option<&[VertexSensitivity]>) {
+        self.sensitivity = sensitivity.map_or_else(Vec::new, <[_]>::to_vec);
+    }
+
+    /// The average of the vertices, which is what the polygon is centred on and rotated
+    /// and scaled about.
+    pub fn centroid(&self) -> Pos2 {
         let mut centroid = Vec2::ZERO;
         for v in &self.vertices {
             centroid += v.to_vec2();
         }
         centroid /= self.vertices.len() as f32;
+        centroid.to_pos2()
+    }
+
+    pub fn centered_vertices(&self) -> Vec<Pos2> {
+        let centroid = self.centroid().to_vec2();
 
         self.vertices.iter().map(|&a| a - centroid).collect()
     }
 
+    pub fn translate(&mut self, offset: Vec2) {
+        for v in &mut self.vertices {
+            *v += offset;
+        }
+    }
+
+    /// Rotates the polygon about its centroid by `angle` radians, clockwise on screen.
+    pub fn rotate(&mut self, angle: f32) {
+        let centroid = self.centroid();
+        let rotation = Rot2::from_angle(angle);
+        self.vertices = self
+            .centered_vertices()
+            .iter()
+            .map(|&v| centroid + rotation * v.to_vec2())
+            .collect();
+    }
+
+    /// Stretches the polygon about its centroid by `factors` along each axis.
+    ///
+    /// A negative factor mirrors the polygon, in which case the vertex order is reversed
+    /// (keeping the first vertex first) so that it stays anti-clockwise.
+    pub fn scale(&mut self, factors: Vec2) {
+        let centroid = self.centroid();
+        self.vertices = self
+            .centered_vertices()
+            .iter()
+            .map(|&v| centroid + v.to_vec2() * factors)
+            .collect();
+
+        if factors.x * factors.y < 0.0
+            && let Some((_, rest)) = self.vertices.split_first_mut()
+        {
+            rest.reverse();
+        }
+    }
+
+    /// Mirrors the polygon in the vertical line through its centroid.
+    pub fn mirror_horizontally(&mut self) {
+        self.scale(vec2(-1.0, 1.0));
+    }
+
+    /// Mirrors the polygon in the horizontal line through its centroid.
+    pub fn mirror_vertically(&mut self) {
+        self.scale(vec2(1.0, -1.0));
+    }
+
     pub fn ui_content(&mut self, ui: &mut egui::Ui) -> egui::Response {
-        let (response, painter) = ui.allocate_painter(Vec2::new(200.0, 200.0), Sense::hover());
+        let (response, painter) = self.view.allocate_painter(ui);
+        let to_screen = self.view.to_screen(response.rect);
 
-        let to_screen = emath::RectTransform::from_to(
-            Rect::from_min_size(Pos2::ZERO, response.rect.size()),
-            response.rect,
-        );
+        // These go underneath the vertex handles, so that vertices can still be grabbed when
+        // they overlap
+        let transform_handles = if self.show_transform_handles {
+            self.transform_handles(ui, &response, to_screen)
+        } else {
+            Vec::new()
+        };
 
         let vertex_radius = 8.0;
+        let grid_spacing = self.view.grid_spacing();
+        let ignore_snapping = ui.input(|i| i.modifiers.shift);
 
-        let vertex_handles: Vec<Shape> = self
-            .vertices
-            .iter_mut()
-            .enumerate()
-            .map(|(i, point)| {
-                let size = Vec2::splat(2.0 * vertex_radius);
+        let num_vertices = self.vertices.len();
+        let mut vertex_handles: Vec<Shape> = Vec::with_capacity(num_vertices);
+        for i in 0..num_vertices {
+            let previous = self
+                .vertices
+                .get((i + num_vertices - 1) % num_vertices)
+                .copied();
+            let Some(point) = self.vertices.get_mut(i) else {
+                continue;
+            };
+            let size = Vec2::splat(2.0 * vertex_radius);
 
-                let point_in_screen = to_screen.transform_pos(*point);
-                let point_rect = Rect::from_center_size(point_in_screen, size);
-                let point_id = response.id.with(i);
-                let point_response = ui.interact(point_rect, point_id, Sense::drag());
+            let point_in_screen = to_screen.transform_pos(*point);
+            let point_rect = Rect::from_center_size(point_in_screen, size);
+            let point_id = response.id.with(i);
+            let point_response = ui.interact(point_rect, point_id, Sense::drag());
 
-                *point += point_response.drag_delta();
-                *point = to_screen.from().clamp(*point);
+            if let Some(pointer) = point_response.interact_pointer_pos() {
+                let pointer_in_world = to_screen.inverse() * pointer;
+                if point_response.drag_started() {
+                    self.drag_offset = *point - pointer_in_world;
+                }
+                if point_response.dragged() {
+                    let dragged_to = pointer_in_world + self.drag_offset;
+                    *point = if ignore_snapping {
+                        dragged_to
+                    } else {
+                        self.snapping.apply(dragged_to, previous, grid_spacing)
+                    };
+                }
+            }
 
-                let point_in_screen = to_screen.transform_pos(*point);
-                let stroke = ui.style().interact(&point_response).fg_stroke;
+            let point_in_screen = to_screen.transform_pos(*point);
+            let stroke = ui.style().interact(&point_response).fg_stroke;
 
-                Shape::circle_stroke(point_in_screen, vertex_radius, stroke)
-            })
-            .collect();
+            vertex_handles.push(Shape::circle_stroke(point_in_screen, vertex_radius, stroke));
+        }
 
         let points_in_screen: Vec<Pos2> = self.vertices.iter().map(|p| to_screen * *p).collect();
 
@@ -85,17 +264,256 @@ impl Polygon {
         painter.add(epaint::RectShape::stroke(
             shape.visual_bounding_rect(),
             0.0,
-            self.bounding_box_stroke,
+            self.active_bounding_box_stroke(),
             egui::StrokeKind::Outside,
         ));
         painter.add(shape);
+        self.paint_sensitivity(&painter, to_screen);
 
+        painter.extend(transform_handles);
         painter.extend(vertex_handles);
+        self.view.paint_cursor_readout(ui, &painter, response.rect);
 
         response
     }
 
-    pub fn ui_readout(&mut self, ui: &mut egui::Ui) {
+    /// Draws an arrow from each vertex along the gradient of the margin, which is the way to
+    /// move it to make the most room, and a circle showing how far it can move before the
+    /// answer flips.
+    fn paint_sensitivity(&self, painter: &egui::Painter, to_screen: RectTransform) {
+        // How long, in points, the arrow for a gradient of one is
+        let arrow_scale = 40.0;
+        let arrow_stroke = Stroke::new(1.5, Color32::from_rgb(230, 160, 40));
+        let tolerance_stroke = Stroke::new(1.0, arrow_stroke.color.linear_multiply(0.5));
+
+        for (vertex, sensitivity) in self.vertices.iter().zip(&self.sensitivity) {
+            let origin = to_screen * *vertex;
+            let gradient = vec2(sensitivity.gradient.x as f32, sensitivity.gradient.y as f32);
+            let direction = to_screen.scale() * gradient;
+            painter.arrow(
+                origin,
+                direction.normalized() * gradient.length() * arrow_scale,
+                arrow_stroke,
+            );
+            if let Some(tolerance) = sensitivity.tolerance {
+                let radius = tolerance as f32 * to_screen.scale().x;
+                painter.circle_stroke(origin, radius, tolerance_stroke);
+            }
+        }
+    }
+
+    /// The bounding box is only faintly drawn, unless it's being used for the transform
+    /// handles.
+    fn active_bounding_box_stroke(&self) -> Stroke {
+        if self.show_transform_handles {
+            Stroke::new(1.0, self.bounding_box_stroke.color)
+        } else {
+            self.bounding_box_stroke
+        }
+    }
+
+    /// Lets the user drag handles on the bounding box to move, rotate and scale the whole
+    /// polygon, and returns the shapes to draw for them.
+    fn transform_handles(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        to_screen: RectTransform,
+    ) -> Vec<Shape> {
+        let handle_radius = 5.0;
+        let rotate_handle_offset = 20.0;
+
+        let bounds = to_screen.transform_rect(Rect::from_points(&self.vertices));
+        let centroid = to_screen * self.centroid();
+        let rotate_handle = bounds.center_top() - vec2(0.0, rotate_handle_offset);
+        let handles = [
+            (centroid, TransformHandle::Move),
+            (rotate_handle, TransformHandle::Rotate),
+            (bounds.left_top(), TransformHandle::ScaleUniform),
+            (bounds.right_top(), TransformHandle::ScaleUniform),
+            (bounds.left_bottom(), TransformHandle::ScaleUniform),
+            (bounds.right_bottom(), TransformHandle::ScaleUniform),
+            (bounds.left_center(), TransformHandle::ScaleX),
+            (bounds.right_center(), TransformHandle::ScaleX),
+            (bounds.center_top(), TransformHandle::ScaleY),
+            (bounds.center_bottom(), TransformHandle::ScaleY),
+        ];
+
+        let mut shapes = vec![Shape::line_segment(
+            [bounds.center_top(), rotate_handle],
+            self.active_bounding_box_stroke(),
+        )];
+        for (idx, (handle, kind)) in handles.into_iter().enumerate() {
+            let handle_rect = Rect::from_center_size(handle, Vec2::splat(2.0 * handle_radius));
+            let handle_response = ui.interact(
+                handle_rect,
+                response.id.with(("transform", idx)),
+                Sense::drag(),
+            );
+
+            if let (true, Some(pointer)) = (
+                handle_response.dragged(),
+                handle_response.interact_pointer_pos(),
+            ) {
+                // Everything relative to the centroid, in world units. The rotate handle
+                // stays above the bounding box rather than turning with the polygon, so it's
+                // turned by however far the pointer has moved since the last frame instead
+                let grabbed = match kind {
+                    TransformHandle::Rotate => pointer - handle_response.drag_delta(),
+                    _ => handle,
+                };
+                let from = (grabbed - centroid) / to_screen.scale();
+                let to = (pointer - centroid) / to_screen.scale();
+                self.apply_handle_drag(kind, from, to);
+            }
+
+            let stroke = ui.style().interact(&handle_response).fg_stroke;
+            shapes.push(match kind {
+                TransformHandle::Move | TransformHandle::Rotate => {
+                    Shape::circle_stroke(handle, handle_radius, stroke)
+                }
+                TransformHandle::ScaleUniform
+                | TransformHandle::ScaleX
+                | TransformHandle::ScaleY => {
+                    Shape::rect_stroke(handle_rect, 0.0, stroke, egui::StrokeKind::Middle)
+                }
+            });
+        }
+        shapes
+    }
+
+    /// Transforms the polygon so that the point `from` (relative to the centroid) is moved to
+    /// `to`, in whichever way `kind` of handle allows.
+    ///
+    /// A scale is skipped if it would collapse the polygon down to nothing, or if the handle
+    /// is (all but) level with the centroid along the axis being scaled, as it is when the
+    /// polygon is flat, so that the factor can't be worked out.
+    fn apply_handle_drag(&mut self, kind: TransformHandle, from: Vec2, to: Vec2) {
+        let from_length = from.length();
+        let factor = |to: f32, from: f32| {
+            (from.abs() > 1e-3 * from_length)
+                .then(|| to / from)
+                .filter(|&factor| factor > MIN_SCALE)
+        };
+
+        match kind {
+            TransformHandle::Move => self.translate(to - from),
+            TransformHandle::Rotate => self.rotate(to.angle() - from.angle()),
+            TransformHandle::ScaleUniform => {
+                if let Some(factor) = factor(to.dot(from) / from_length, from_length) {
+                    self.scale(Vec2::splat(factor));
+                }
+            }
+            TransformHandle::ScaleX => {
+                if let Some(factor) = factor(to.x, from.x) {
+                    self.scale(vec2(factor, 1.0));
+                }
+            }
+            TransformHandle::ScaleY => {
+                if let Some(factor) = factor(to.y, from.y) {
+                    self.scale(vec2(1.0, factor));
+                }
+            }
+        }
+    }
+
+    /// Numeric versions of the transform handles, plus mirroring.
+    pub fn ui_transform_controls(&mut self, ui: &mut egui::Ui) {
+        let inputs = &mut self.transform_inputs;
+        let mut rotate_by = None;
+        let mut scale_by = None;
+        let mut move_by = None;
+
+        Grid::new(ui.id().with("transform_controls"))
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.add(DragValue::new(&mut inputs.angle).speed(1.0).suffix("°"));
+                if ui.button("Rotate").clicked() {
+                    rotate_by = Some(inputs.angle.to_radians());
+                }
+                ui.end_row();
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        DragValue::new(&mut inputs.scale.x)
+                            .range(MIN_SCALE..=f32::INFINITY)
+                            .speed(0.01)
+                            .prefix("x "),
+                    );
+                    if inputs.uniform_scale {
+                        inputs.scale.y = inputs.scale.x;
+                    } else {
+                        ui.add(
+                            DragValue::new(&mut inputs.scale.y)
+                                .range(MIN_SCALE..=f32::INFINITY)
+                                .speed(0.01)
+                                .prefix("y "),
+                        );
+                    }
+                    ui.checkbox(&mut inputs.uniform_scale, "Uniform");
+                });
+                if ui.button("Scale").clicked() {
+                    scale_by = Some(inputs.scale);
+                }
+                ui.end_row();
+
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut inputs.offset.x).prefix("x "));
+                    ui.add(DragValue::new(&mut inputs.offset.y).prefix("y "));
+                });
+                if ui.button("Move").clicked() {
+                    move_by = Some(inputs.offset);
+                }
+                ui.end_row();
+            });
+
+        if let Some(angle) = rotate_by {
+            self.rotate(angle);
+        }
+        if let Some(factors) = scale_by {
+            self.scale(factors);
+        }
+        if let Some(offset) = move_by {
+            self.translate(offset);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Mirror ↔").clicked() {
+                self.mirror_horizontally();
+            }
+            if ui.button("Mirror ↕").clicked() {
+                self.mirror_vertically();
+            }
+        });
+    }
+
+    pub fn ui_canvas_controls(&mut self, ui: &mut egui::Ui) {
+        self.view.ui_controls(ui, &self.vertices);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.snapping.to_grid, "Snap to grid");
+            ui.checkbox(&mut self.snapping.to_angle, "Snap angle");
+            ui.add_enabled(
+                self.snapping.to_angle,
+                egui::DragValue::new(&mut self.snapping.angle_step)
+                    .range(1.0..=90.0)
+                    .suffix("°"),
+            );
+        })
+        .response
+        .on_hover_text("Hold Shift while dragging to ignore snapping");
+        ui.checkbox(&mut self.show_transform_handles, "Transform handles");
+        egui::CollapsingHeader::new("Transform")
+            .id_salt(ui.id().with("transform"))
+            .show(ui, |ui| {
+                self.ui_transform_controls(ui);
+            });
+    }
+
+    pub fn properties(&self) -> PolygonProperties {
+        PolygonProperties::of(&self.vertices)
+    }
+
+    pub fn ui_readout(&self, ui: &mut egui::Ui) {
         for (idx, vertex) in self.vertices.iter().enumerate() {
             ui.label(format!(
                 "Vertex {}: ({:.2}, {:.2})",
@@ -104,5 +522,70 @@ impl Polygon {
                 vertex.y
             ));
         }
+
+        ui.add_space(4.0);
+        let properties = self.properties();
+        Grid::new(ui.id().with("properties"))
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Area");
+                ui.label(format!("{:.2}", properties.area));
+                ui.end_row();
+
+                ui.label("Perimeter");
+                ui.label(format!("{:.2}", properties.perimeter));
+                ui.end_row();
+
+                ui.label("Centroid");
+                ui.label(format!(
+                    "({:.2}, {:.2})",
+                    properties.centroid.x, properties.centroid.y
+                ));
+                ui.end_row();
+
+                ui.label("Min. width");
+                ui.label(format!(
+                    "{:.2} at {:.1}°",
+                    properties.min_width,
+                    properties.min_width_direction.angle().to_degrees()
+                ));
+                ui.end_row();
+
+                ui.label("Diameter");
+                ui.label(format!("{:.2}", properties.diameter));
+                ui.end_row();
+
+                ui.label("Inradius");
+                ui.label(format!("{:.2}", properties.inradius));
+                ui.end_row();
+
+                ui.label("Circumradius");
+                ui.label(format!("{:.2}", properties.circumradius));
+                ui.end_row();
+
+                ui.label("Angles");
+                ui.label(
+                    properties
+                        .interior_angles
+                        .iter()
+                        .map(|angle| format!("{:.1}°", angle.to_degrees()))
+                        .join(", "),
+                );
+                ui.end_row();
+
+                let symmetry = &properties.symmetry;
+                ui.label("Symmetry");
+                ui.label(symmetry.group_name()).on_hover_text(format!(
+                    "{}-fold rotational symmetry, {} mirror line{}",
+                    symmetry.order,
+                    symmetry.mirror_axes.len(),
+                    if symmetry.mirror_axes.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    }
+                ));
+                ui.end_row();
+            });
     }
 }
@@ -0,0 +1,100 @@
+use egui::{Grid, ProgressBar};
+
+use crate::{
+    fit::{Sensitivity, SensitivityAnalysis},
+    widgets::fit_calculator::SolveInputs,
+};
+
+/// How much moving each vertex changes the margin, and how far it can move before the
+/// answer flips.
+#[derive(Default)]
+pub struct SensitivityPanel {
+    enabled: bool,
+    sensitivity: Option<Sensitivity>,
+    /// The analysis under way, a vertex a frame, since something changed. Until it's done,
+    /// [`Self::sensitivity`] is what was worked out before.
+    analysis: Option<SensitivityAnalysis>,
+    /// What the latest analysis was started for, as it's slow enough that it's only redone
+    /// when something changes.
+    inputs: Option<SolveInputs>,
+}
+
+impl SensitivityPanel {
+    /// Starts working out the sensitivity again if it's wanted and anything has changed, and
+    /// takes the analysis under way one vertex further. While `busy`, as when anything is
+    /// being dragged, this waits until it's done.
+    pub fn update(&mut self, ctx: &egui::Context, inputs: SolveInputs, busy: bool) {
+        if !self.enabled {
+            self.sensitivity = None;
+            self.analysis = None;
+            self.inputs = None;
+            return;
+        }
+        if busy {
+            return;
+        }
+
+        if self.inputs.as_ref() != Some(&inputs) {
+            self.analysis = Some(SensitivityAnalysis::new(
+                inputs.solver,
+                &inputs.outer_vertices,
+                &inputs.inner_vertices,
+                &inputs.settings,
+            ));
+            self.inputs = Some(inputs);
+        }
+        if let Some(analysis) = &mut self.analysis {
+            analysis.run_next();
+            if analysis.is_finished() {
+                self.sensitivity = analysis.sensitivity();
+                self.analysis = None;
+            } else {
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    pub fn sensitivity(&self) -> Option<&Sensitivity> {
+        self.sensitivity.as_ref()
+    }
+
+    /// The switch, and a table of every vertex's gradient and tolerance.
+    pub fn ui_content(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Sensitivity").on_hover_text(
+            "Show which way to move each vertex to make more room, and how far it can move \
+             before the answer flips. This solves again for every vertex, so can be slow",
+        );
+        if let Some(analysis) = &self.analysis {
+            ui.add(ProgressBar::new(analysis.progress()).show_percentage());
+        }
+        let Some(sensitivity) = &self.sensitivity else {
+            return;
+        };
+
+        ui.weak(format!("Margin: {:.3}", sensitivity.margin));
+        Grid::new("sensitivity").striped(true).show(ui, |ui| {
+            ui.label("Vertex");
+            ui.label("Gradient");
+            ui.label("|Gradient|");
+            ui.label("Tolerance");
+            ui.end_row();
+
+            for (name, vertices) in [("A", &sensitivity.outer), ("B", &sensitivity.inner)] {
+                for (i, vertex) in vertices.iter().enumerate() {
+                    ui.label(format!("{name}{}", i + 1));
+                    ui.label(format!(
+                        "({:.3}, {:.3})",
+                        vertex.gradient.x, vertex.gradient.y
+                    ));
+                    ui.label(format!("{:.3}", vertex.gradient.norm()));
+                    ui.label(
+                        vertex
+                            .tolerance
+                            .map_or("–".to_owned(), |tolerance| format!("{tolerance:.3}")),
+                    );
+                    ui.end_row();
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,286 @@
+use egui::{Color32, ComboBox, DragValue, Pos2, ProgressBar};
+use egui_plot::{Line, Plot, PlotPoints, Points};
+
+use crate::{
+    fit::{ParameterSweep, SolverKind, SolverSettings, SweepOutcome, SweepParameter, Which},
+    widgets::degrees_drag_value,
+};
+
+/// How many values of the parameter are solved for each frame while a sweep is running.
+const STEPS_PER_FRAME: usize = 2;
+
+/// The colour that each [`SweepOutcome`] is shown in, on the sweep's plot and the map.
+pub fn outcome_colour(outcome: SweepOutcome) -> Color32 {
+    match outcome {
+        SweepOutcome::Fit => Color32::from_rgb(100, 200, 100),
+        SweepOutcome::NoFit => Color32::from_rgb(200, 100, 100),
+        SweepOutcome::NotConvex => Color32::GRAY,
+        SweepOutcome::Unsupported => Color32::from_rgb(200, 160, 60),
+    }
+}
+
+/// Sweeps a shape parameter over a range, solving at each step, and plots how the margin
+/// and the best angle change with it.
+pub struct SweepPanel {
+    picker: ParameterPicker,
+    num_steps: usize,
+    sweep: Option<ParameterSweep>,
+}
+
+impl Default for SweepPanel {
+    fn default() -> Self {
+        Self {
+            picker: ParameterPicker::new(SweepParameter::default(), (0.5, 2.0)),
+            num_steps: 50,
+            sweep: None,
+        }
+    }
+}
+
+impl SweepPanel {
+    /// The controls and the plots. Both sets of vertices should be centred on their
+    /// centroids.
+    pub fn ui_content(
+        &mut self,
+        ui: &mut egui::Ui,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+        solver: SolverKind,
+        settings: &SolverSettings,
+    ) {
+        self.picker.ui(ui, "sweep", outer_vertices, inner_vertices);
+        ui.horizontal(|ui| {
+            ui.label("In");
+            ui.add(DragValue::new(&mut self.num_steps).range(1..=1000));
+            ui.label("steps");
+        });
+
+        let running = self
+            .sweep
+            .as_ref()
+            .is_some_and(|sweep| !sweep.is_finished());
+        ui.horizontal(|ui| {
+            if running {
+                if ui.button("Stop").clicked() {
+                    self.sweep = None;
+                }
+            } else if ui.button("Run").clicked() {
+                self.sweep = Some(ParameterSweep::new(
+                    self.picker.parameter,
+                    self.picker.range,
+                    self.num_steps,
+                    outer_vertices,
+                    inner_vertices,
+                    solver,
+                    settings,
+                ));
+            }
+            if let Some(sweep) = &self.sweep
+                && ui.button("Copy as CSV").clicked()
+            {
+                ui.ctx().copy_text(sweep.to_csv());
+            }
+        });
+
+        let Some(sweep) = &mut self.sweep else {
+            return;
+        };
+        if !sweep.is_finished() {
+            for _ in 0..STEPS_PER_FRAME {
+                sweep.run_next();
+            }
+            ui.add(ProgressBar::new(sweep.progress()).show_percentage());
+            ui.ctx().request_repaint();
+        }
+        Self::ui_plots(ui, sweep);
+    }
+
+    /// The margin against the parameter, with each step marked by whether B fits, and the
+    /// best angle against the parameter below it. Steps where nothing could be solved are
+    /// marked along zero.
+    fn ui_plots(ui: &mut egui::Ui, sweep: &ParameterSweep) {
+        let points = sweep.points();
+        let margin_line = Line::new(
+            "Margin",
+            points
+                .iter()
+                .filter_map(|point| point.margin.map(|margin| [point.value, margin]))
+                .collect::<PlotPoints<'_>>(),
+        )
+        .color(Color32::from_rgb(200, 100, 100));
+        let outcomes = SweepOutcome::ALL.map(|outcome| {
+            let marked = points
+                .iter()
+                .filter(|point| point.outcome == outcome)
+                .map(|point| [point.value, point.margin.unwrap_or(0.0)])
+                .collect::<PlotPoints<'_>>();
+            Points::new(outcome.name(), marked)
+                .color(outcome_colour(outcome))
+                .radius(3.0)
+        });
+        // Only points for the angle, as it jumps when it wraps round
+        let angles = Points::new(
+            "Best angle",
+            points
+                .iter()
+                .filter_map(|point| point.angle.map(|angle| [point.value, angle.to_degrees()]))
+                .collect::<PlotPoints<'_>>(),
+        )
+        .radius(2.0);
+
+        let link = ui.id().with("sweep_plots");
+        Plot::new("sweep_margin")
+            .width(600.0)
+            .height(200.0)
+            .y_axis_label("Margin")
+            .link_axis(link, [true, false])
+            .show(ui, |plot_ui| {
+                plot_ui.hline(egui_plot::HLine::new("Zero", 0.0).color(Color32::GRAY));
+                plot_ui.line(margin_line);
+                for points in outcomes {
+                    plot_ui.points(points);
+                }
+            });
+        Plot::new("sweep_angle")
+            .width(600.0)
+            .height(150.0)
+            .x_axis_label(sweep.parameter().name())
+            .y_axis_label("Angle (°)")
+            .link_axis(link, [true, false])
+            .show(ui, |plot_ui| plot_ui.points(angles));
+    }
+}
+
+/// A shape parameter, and the range of values to sweep it over.
+pub struct ParameterPicker {
+    pub parameter: SweepParameter,
+    pub range: (f64, f64),
+}
+
+impl ParameterPicker {
+    pub fn new(parameter: SweepParameter, range: (f64, f64)) -> Self {
+        Self { parameter, range }
+    }
+
+    /// The controls for the parameter and its range. `id_salt` tells apart pickers in the
+    /// same place. Both sets of vertices should be centred on their centroids.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        id_salt: &str,
+        outer_vertices: &[Pos2],
+        inner_vertices: &[Pos2],
+    ) {
+        // Changing the vertex or the direction keeps the range, as it's in proportion to the
+        // whole polygon anyway
+        let kind =
+            |parameter: SweepParameter| (std::mem::discriminant(&parameter), parameter.polygon());
+        let before = kind(self.parameter);
+        self.ui_parameter(ui, id_salt, outer_vertices.len(), inner_vertices.len());
+        if kind(self.parameter) != before {
+            self.range = self.parameter.default_range(outer_vertices, inner_vertices);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("From");
+            ui.add(DragValue::new(&mut self.range.0).speed(0.01));
+            ui.label("to");
+            ui.add(DragValue::new(&mut self.range.1).speed(0.01));
+        });
+    }
+
+    /// Which parameter to sweep, and of which polygon.
+    fn ui_parameter(
+        &mut self,
+        ui: &mut egui::Ui,
+        id_salt: &str,
+        num_outer: usize,
+        num_inner: usize,
+    ) {
+        let polygon = self.parameter.polygon();
+        ui.horizontal(|ui| {
+            ComboBox::from_id_salt((id_salt, "parameter"))
+                .selected_text(match self.parameter {
+                    SweepParameter::VertexOffset { .. } => "Vertex offset",
+                    SweepParameter::AspectRatio(_) => "Aspect ratio",
+                    SweepParameter::Scale(_) => "Scale",
+                })
+                .show_ui(ui, |ui| {
+                    let options = [
+                        (
+                            SweepParameter::VertexOffset {
+                                polygon,
+                                vertex: 0,
+                                angle: 0.0,
+                            },
+                            "Vertex offset",
+                            "Move one vertex along a line",
+                        ),
+                        (
+                            SweepParameter::AspectRatio(polygon),
+                            "Aspect ratio",
+                            "Stretch the polygon up or down",
+                        ),
+                        (
+                            SweepParameter::Scale(polygon),
+                            "Scale",
+                            "Scale the whole polygon",
+                        ),
+                    ];
+                    for (option, name, description) in options {
+                        let selected = std::mem::discriminant(&self.parameter)
+                            == std::mem::discriminant(&option);
+                        if ui
+                            .selectable_label(selected, name)
+                            .on_hover_text(description)
+                            .clicked()
+                            && !selected
+                        {
+                            self.parameter = option;
+                        }
+                    }
+                });
+            ui.label("of");
+            let mut chosen = polygon;
+            ComboBox::from_id_salt((id_salt, "polygon"))
+                .selected_text(chosen.name())
+                .width(40.0)
+                .show_ui(ui, |ui| {
+                    for which in Which::ALL {
+                        ui.selectable_value(&mut chosen, which, which.name());
+                    }
+                });
+            if chosen != polygon {
+                self.parameter = match self.parameter {
+                    SweepParameter::VertexOffset { angle, .. } => SweepParameter::VertexOffset {
+                        polygon: chosen,
+                        vertex: 0,
+                        angle,
+                    },
+                    SweepParameter::AspectRatio(_) => SweepParameter::AspectRatio(chosen),
+                    SweepParameter::Scale(_) => SweepParameter::Scale(chosen),
+                };
+            }
+        });
+
+        if let SweepParameter::VertexOffset {
+            polygon,
+            vertex,
+            angle,
+        } = &mut self.parameter
+        {
+            let num_vertices = match polygon {
+                Which::Outer => num_outer,
+                Which::Inner => num_inner,
+            };
+            let mut number = *vertex + 1;
+            ui.horizontal(|ui| {
+                ui.label("Vertex");
+                ui.add(DragValue::new(&mut number).range(1..=num_vertices.max(1)));
+                ui.label("along");
+                degrees_drag_value(ui, angle);
+            });
+            *vertex = number.saturating_sub(1);
+        }
+    }
+}